@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS progress (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    reading_idx INTEGER NOT NULL,
+    book TEXT NOT NULL,
+    chapter INTEGER NOT NULL,
+    read_on DATE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, reading_idx, book, chapter, read_on),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS progress_user_read_on ON progress (user_id, read_on);
@@ -0,0 +1,2 @@
+ALTER TABLE readings ADD COLUMN catch_up BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE readings ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+ALTER TABLE readings ADD COLUMN start_date DATE;
+ALTER TABLE readings ADD COLUMN paused_on DATE;
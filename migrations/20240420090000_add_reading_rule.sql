@@ -0,0 +1 @@
+ALTER TABLE readings ADD COLUMN rule TEXT NOT NULL DEFAULT 'cycle';
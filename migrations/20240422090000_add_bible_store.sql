@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS translations (
+    code TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS verses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    translation TEXT NOT NULL,
+    book INTEGER NOT NULL,
+    chapter INTEGER NOT NULL,
+    verse INTEGER NOT NULL,
+    title TEXT,
+    text TEXT NOT NULL,
+    UNIQUE (translation, book, chapter, verse),
+    FOREIGN KEY (translation) REFERENCES translations(code) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id INTEGER PRIMARY KEY NOT NULL,
+    translation TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
@@ -0,0 +1,21 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS verses_fts USING fts5 (
+    text,
+    content = 'verses',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS verses_fts_insert AFTER INSERT ON verses BEGIN
+    INSERT INTO verses_fts (rowid, text) VALUES (new.id, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS verses_fts_delete AFTER DELETE ON verses BEGIN
+    INSERT INTO verses_fts (verses_fts, rowid, text) VALUES ('delete', old.id, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS verses_fts_update AFTER UPDATE ON verses BEGIN
+    INSERT INTO verses_fts (verses_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    INSERT INTO verses_fts (rowid, text) VALUES (new.id, new.text);
+END;
+
+INSERT INTO verses_fts (verses_fts) VALUES ('rebuild');
@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    entry_date DATE NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, entry_date),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS journal_fts USING fts5 (
+    body,
+    content = 'journal',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS journal_fts_insert AFTER INSERT ON journal BEGIN
+    INSERT INTO journal_fts (rowid, body) VALUES (new.id, new.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS journal_fts_delete AFTER DELETE ON journal BEGIN
+    INSERT INTO journal_fts (journal_fts, rowid, body) VALUES ('delete', old.id, old.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS journal_fts_update AFTER UPDATE ON journal BEGIN
+    INSERT INTO journal_fts (journal_fts, rowid, body) VALUES ('delete', old.id, old.body);
+    INSERT INTO journal_fts (rowid, body) VALUES (new.id, new.body);
+END;
@@ -0,0 +1,8 @@
+-- Secret of the per-user iCalendar feed URL, stored as SHA-256 like API tokens
+-- and sessions. Deleting the row revokes it.
+CREATE TABLE IF NOT EXISTS calendar_tokens (
+    user_id INTEGER PRIMARY KEY NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
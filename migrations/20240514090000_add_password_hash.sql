@@ -0,0 +1,2 @@
+-- Argon2 hash for users of the local login, NULL for everyone else.
+ALTER TABLE users ADD COLUMN password_hash TEXT;
@@ -0,0 +1,10 @@
+-- Single-use links for the email login. Only the hash of the token is stored.
+CREATE TABLE IF NOT EXISTS login_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email VARCHAR(255) NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS login_links_email ON login_links (email);
@@ -0,0 +1,5 @@
+//! JSON API for clients other than the htmx pages, sharing their model code
+//! and authentication. Each version lives in its own module.
+
+pub mod tokens;
+pub mod v1;
@@ -0,0 +1,305 @@
+use crate::{
+    auth::User,
+    brp::model::UserSettings,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        hx::HxCfg,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg, ButtonType},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Form,
+};
+use chrono::NaiveDateTime;
+use maud::{html, Markup};
+use rand::RngCore;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Prefix of every token, so leaked ones are easy to recognise.
+const TOKEN_PREFIX: &str = "brp_";
+
+/// What a token may do. Browser sessions can do everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenScope {
+    /// Only `GET` endpoints.
+    Read,
+    Write,
+}
+
+impl TokenScope {
+    pub fn all() -> [TokenScope; 2] {
+        [TokenScope::Read, TokenScope::Write]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenScope::Read => "Read only",
+            TokenScope::Write => "Read and write",
+        }
+    }
+}
+
+impl Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenScope::Read => write!(f, "read"),
+            TokenScope::Write => write!(f, "write"),
+        }
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(TokenScope::Read),
+            "write" => Ok(TokenScope::Write),
+            _ => Err("unknown token scope"),
+        }
+    }
+}
+
+/// A personal API token, without the secret itself.
+#[derive(Debug)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub scope: TokenScope,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+fn hash_token(token: &str) -> String {
+    sha256::digest(token)
+}
+
+impl ApiToken {
+    pub async fn all_from_user(pool: &SqlitePool, user_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"SELECT id AS "id!", name, scope,
+                created_at AS "created_at!: NaiveDateTime",
+                last_used_at AS "last_used_at: NaiveDateTime"
+            FROM api_tokens WHERE user_id = ? ORDER BY id DESC"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rec| {
+            Some(Self {
+                id: rec.id,
+                name: rec.name,
+                scope: rec.scope.parse().ok()?,
+                created_at: rec.created_at,
+                last_used_at: rec.last_used_at,
+            })
+        })
+        .collect())
+    }
+
+    /// Create a token and return its secret, which isn't stored.
+    pub async fn create(
+        pool: &SqlitePool,
+        user_id: i64,
+        name: &str,
+        scope: TokenScope,
+    ) -> Result<String, sqlx::Error> {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = format!("{TOKEN_PREFIX}{}", hex::encode(bytes));
+
+        let hash = hash_token(&token);
+        let scope = scope.to_string();
+        sqlx::query!(
+            "INSERT INTO api_tokens (user_id, name, token_hash, scope) VALUES (?1, ?2, ?3, ?4)",
+            user_id,
+            name,
+            hash,
+            scope
+        )
+        .execute(pool)
+        .await?;
+        Ok(token)
+    }
+
+    pub async fn revoke(pool: &SqlitePool, user_id: i64, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM api_tokens WHERE id = ? AND user_id = ?",
+            id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The user and scope of a token, recording that it was used.
+    pub async fn authenticate(
+        pool: &SqlitePool,
+        token: &str,
+    ) -> Result<Option<(User, TokenScope)>, sqlx::Error> {
+        let hash = hash_token(token);
+        let rec = sqlx::query!(
+            r#"SELECT api_tokens.id AS "id!", api_tokens.scope, users.id AS "user_id!", users.email
+            FROM api_tokens
+            JOIN users ON users.id = api_tokens.user_id
+            WHERE api_tokens.token_hash = ?"#,
+            hash
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some(rec) = rec else {
+            return Ok(None);
+        };
+        let Ok(scope) = rec.scope.parse() else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?",
+            rec.id
+        )
+        .execute(pool)
+        .await?;
+        Ok(Some((
+            User {
+                email: rec.email,
+                id: rec.user_id,
+            },
+            scope,
+        )))
+    }
+}
+
+/// The tokens section of the settings page. `created` is the secret of a
+/// token just created, shown this one time only.
+pub fn fragment_api_tokens(tokens: &[ApiToken], created: Option<&str>, locale: Locale) -> Markup {
+    html! {
+        section id="api-tokens" {
+            h2 class="font-bold text-md mb-1" { (locale.t("API tokens")) }
+            p class="text-sm text-foreground/60 mb-4" {
+                (locale.t("Tokens let scripts and other apps use the JSON API on your behalf, sent as an \"Authorization: Bearer\" header."))
+            }
+            @if let Some(token) = created {
+                div class="flex flex-col gap-1 mb-4 p-3 border border-green-600 rounded-sm" {
+                    p class="text-sm text-green-600" {
+                        (locale.t("Copy the token now, it won't be shown again."))
+                    }
+                    input type="text" readonly value=(token) "@click"="$el.select()" x-data
+                        class="w-full text-sm font-mono rounded-xs border-border bg-background py-1.5";
+                }
+            }
+            @if !tokens.is_empty() {
+                table class="w-full text-sm mb-4" {
+                    thead {
+                        tr class="border-b border-border text-left" {
+                            th class="py-1" { (locale.t("Name")) }
+                            th class="py-1" { (locale.t("Access")) }
+                            th class="py-1" { (locale.t("Created")) }
+                            th class="py-1" { (locale.t("Last used")) }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        @for token in tokens {
+                            tr class="border-b border-border/50" {
+                                td class="py-1" { (token.name) }
+                                td class="py-1" { (locale.t(token.scope.name())) }
+                                td class="py-1" { (token.created_at.format("%d/%m/%Y")) }
+                                td class="py-1" {
+                                    @match token.last_used_at {
+                                        Some(used) => (used.format("%d/%m/%Y %H:%M")),
+                                        None => (locale.t("Never")),
+                                    }
+                                }
+                                td class="py-1 text-right" {
+                                    button type="button" class="px-2 py-1 text-xs text-red-600 hover:bg-red-50"
+                                        hx-post=(format!("/settings/tokens/{}/revoke", token.id))
+                                        hx-target="#api-tokens"
+                                        hx-swap="outerHTML"
+                                        hx-confirm=(locale.t("Revoke this token? Apps using it stop working."))
+                                    { (locale.t("Revoke")) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            form class="flex gap-2 items-center"
+                hx-post="/settings/tokens"
+                hx-target="#api-tokens"
+                hx-swap="outerHTML"
+            {
+                input type="text" name="name" required maxlength="100" placeholder=(locale.t("Token name, e.g. Phone widget"))
+                    class="flex-1 min-w-0 text-sm rounded-xs border-border bg-background py-1.5";
+                select name="scope" class="text-sm rounded-xs border-border bg-background py-1.5" {
+                    @for scope in TokenScope::all() {
+                        option value=(scope) { (locale.t(scope.name())) }
+                    }
+                }
+                (ui_button(html! { (locale.t("Create token")) },
+                    &ButtonCfg::new().with_color(Color::Alternative).with_type(ButtonType::Submit),
+                    &HxCfg::new()
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    name: String,
+    scope: String,
+}
+
+pub async fn post_token(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<CreateTokenRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let name = form.name.trim();
+            let created = match (name.is_empty(), form.scope.parse::<TokenScope>()) {
+                (false, Ok(scope)) => {
+                    Some(ApiToken::create(&state.db, user.id, name, scope).await?)
+                }
+                _ => None,
+            };
+            let tokens = ApiToken::all_from_user(&state.db, user.id).await?;
+            Ok(fragment_api_tokens(&tokens, created.as_deref(), locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+pub async fn post_revoke_token(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            ApiToken::revoke(&state.db, user.id, id).await?;
+            let tokens = ApiToken::all_from_user(&state.db, user.id).await?;
+            Ok(fragment_api_tokens(&tokens, None, locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
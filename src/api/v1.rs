@@ -0,0 +1,604 @@
+use crate::{
+    api::tokens::TokenScope,
+    auth::{ApiUser, User},
+    brp::{
+        books::Passage,
+        content::{ChapterDispatcher, ChapterError},
+        model::{ProgressEntry, Reading, UserDates, UserProgress, UserReadings, UserSettings},
+        reference::parse_book,
+    },
+    errors::ApiError,
+    i18n::Locale,
+    utils::today_naive_date,
+    AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    IntoParams, Modify, OpenApi, ToSchema,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Bible reading plan API",
+        version = "1",
+        description = "JSON access to the reading plan. Requests are authenticated with a personal API token, created in the settings, or like the web app."
+    ),
+    paths(
+        get_plan,
+        get_chapter,
+        get_readings,
+        get_dates,
+        put_dates,
+        get_progress,
+        put_reading_progress
+    ),
+    components(schemas(
+        PlanJson,
+        ListPlanJson,
+        PassageJson,
+        ChapterJson,
+        VerseJson,
+        ReadingJson,
+        DatesJson,
+        DatesRequest,
+        ProgressJson,
+        ProgressEntryJson,
+        ReadRequest,
+        ErrorJson
+    )),
+    modifiers(&BearerToken),
+    security(("api_token" = []))
+)]
+pub struct ApiDoc;
+
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "api_token",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+/// Routes of `/api/v1`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(get_openapi))
+        .route("/plan", get(get_plan))
+        .route("/chapters/:book/:chapter", get(get_chapter))
+        .route("/readings", get(get_readings))
+        .route("/readings/:index/progress", put(put_reading_progress))
+        .route("/dates", get(get_dates).put(put_dates))
+        .route("/progress", get(get_progress))
+}
+
+#[derive(Debug, Error)]
+pub enum V1Error {
+    #[error("Not logged in or unknown API token")]
+    Unauthorized,
+
+    #[error("The API token is read only")]
+    Forbidden,
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Internal(#[from] ApiError),
+}
+
+impl From<sqlx::Error> for V1Error {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Internal(e.into())
+    }
+}
+
+impl From<ChapterError> for V1Error {
+    fn from(e: ChapterError) -> Self {
+        match e {
+            ChapterError::NotFound(book, chapter) => Self::NotFound(format!(
+                "{book} {chapter} is not available in this translation"
+            )),
+            e => Self::Internal(e.into()),
+        }
+    }
+}
+
+impl IntoResponse for V1Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Internal(e) => {
+                tracing::error!("api: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let error = ErrorJson {
+            error: self.to_string(),
+        };
+        (status, Json(error)).into_response()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorJson {
+    error: String,
+}
+
+fn logged_in(user: Option<ApiUser>) -> Result<User, V1Error> {
+    user.map(|api_user| api_user.user)
+        .ok_or(V1Error::Unauthorized)
+}
+
+/// Like `logged_in`, for endpoints that change data.
+fn can_write(user: Option<ApiUser>) -> Result<User, V1Error> {
+    match user {
+        Some(ApiUser {
+            user,
+            scope: TokenScope::Write,
+        }) => Ok(user),
+        Some(_) => Err(V1Error::Forbidden),
+        None => Err(V1Error::Unauthorized),
+    }
+}
+
+/// A passage in a book, verses are `null` for whole chapters.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PassageJson {
+    /// English book name, e.g. "1 Corinthians".
+    book: String,
+    chapter: i64,
+    verse: Option<u32>,
+    end_chapter: i64,
+    end_verse: Option<u32>,
+    /// e.g. "Psalms 119:1-32".
+    label: String,
+}
+
+impl From<&Passage> for PassageJson {
+    fn from(passage: &Passage) -> Self {
+        Self {
+            book: passage.book.to_string(),
+            chapter: passage.chapter,
+            verse: passage.verse,
+            end_chapter: passage.end_chapter,
+            end_verse: passage.end_verse,
+            label: passage.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlanJson {
+    /// The date being read, today shifted by the user's offset.
+    date: NaiveDate,
+    lists: Vec<ListPlanJson>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListPlanJson {
+    index: usize,
+    label: String,
+    /// Empty on rest days.
+    portion: Vec<PassageJson>,
+    read: bool,
+    days_behind: i64,
+    catch_up: bool,
+    paused: bool,
+}
+
+impl ListPlanJson {
+    fn new(index: usize, reading: &Reading, dates: &UserDates, progress: &UserProgress) -> Self {
+        let portion = reading.portion(dates);
+        Self {
+            index,
+            label: reading.label(Locale::En),
+            read: progress.is_portion_read(index, &portion),
+            portion: portion.iter().map(PassageJson::from).collect(),
+            days_behind: reading.days_behind(dates),
+            catch_up: reading.catch_up,
+            paused: reading.paused_on.is_some(),
+        }
+    }
+}
+
+/// The portion of every reading list due on the reading date.
+#[utoipa::path(
+    get,
+    path = "/api/v1/plan",
+    tag = "plan",
+    responses(
+        (status = 200, body = PlanJson),
+        (status = 401, body = ErrorJson)
+    )
+)]
+pub async fn get_plan(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+) -> Result<Json<PlanJson>, V1Error> {
+    let user = logged_in(user)?;
+    let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+    let readings = UserReadings::from_user(&state.db, user.id).await;
+    let progress = UserProgress::from_user(&state.db, user.id, dates.reading_date()).await?;
+
+    Ok(Json(PlanJson {
+        date: dates.reading_date(),
+        lists: readings
+            .readings
+            .iter()
+            .enumerate()
+            .map(|(index, reading)| ListPlanJson::new(index, reading, &dates, &progress))
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChapterJson {
+    translation: String,
+    book: String,
+    chapter: i64,
+    verses: Vec<VerseJson>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerseJson {
+    number: u32,
+    /// Section heading shown above the verse.
+    title: Option<String>,
+    text: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChapterQuery {
+    /// Translation code, defaults to the user's translation.
+    translation: Option<String>,
+}
+
+/// The text of a chapter.
+#[utoipa::path(
+    get,
+    path = "/api/v1/chapters/{book}/{chapter}",
+    tag = "bible",
+    params(
+        ("book" = String, Path, description = "Book name or abbreviation, English or Indonesian"),
+        ("chapter" = i64, Path, description = "Chapter number, from 1"),
+        ChapterQuery
+    ),
+    responses(
+        (status = 200, body = ChapterJson),
+        (status = 401, body = ErrorJson),
+        (status = 404, body = ErrorJson)
+    )
+)]
+pub async fn get_chapter(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+    Path((book, chapter)): Path<(String, i64)>,
+    Query(query): Query<ChapterQuery>,
+) -> Result<Json<ChapterJson>, V1Error> {
+    let user = logged_in(user)?;
+    let book =
+        parse_book(&book).ok_or_else(|| V1Error::NotFound(format!("Unknown book \"{book}\"")))?;
+    if chapter < 1 || chapter > book.total_chapters() as i64 {
+        return Err(V1Error::NotFound(format!(
+            "{book} has no chapter {chapter}"
+        )));
+    }
+
+    let settings = UserSettings::from_user(&state.db, user.id).await?;
+    let translation = state.bibles.get(
+        query
+            .translation
+            .as_deref()
+            .or(settings.translation.as_deref()),
+    );
+    let content = translation
+        .source
+        .get_chapter(&book, chapter as usize)
+        .await?;
+
+    Ok(Json(ChapterJson {
+        translation: translation.code.clone(),
+        book: book.to_string(),
+        chapter,
+        verses: content
+            .verses
+            .verse
+            .into_iter()
+            .map(|verse| VerseJson {
+                number: verse.number,
+                title: verse.title,
+                text: verse.text,
+            })
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadingJson {
+    index: usize,
+    books: Vec<String>,
+    /// "cycle", "sections" or "spread:{days}".
+    rule: String,
+    catch_up: bool,
+    /// Portions read while in catch-up mode.
+    position: i64,
+    /// Overrides the global start date.
+    start_date: Option<NaiveDate>,
+    paused_on: Option<NaiveDate>,
+}
+
+/// The reading lists.
+#[utoipa::path(
+    get,
+    path = "/api/v1/readings",
+    tag = "readings",
+    responses(
+        (status = 200, body = [ReadingJson]),
+        (status = 401, body = ErrorJson)
+    )
+)]
+pub async fn get_readings(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+) -> Result<Json<Vec<ReadingJson>>, V1Error> {
+    let user = logged_in(user)?;
+    let readings = UserReadings::from_user(&state.db, user.id).await;
+
+    Ok(Json(
+        readings
+            .readings
+            .into_iter()
+            .enumerate()
+            .map(|(index, reading)| ReadingJson {
+                index,
+                books: reading.books.iter().map(|b| b.to_string()).collect(),
+                rule: reading.rule.to_string(),
+                catch_up: reading.catch_up,
+                position: reading.position,
+                start_date: reading.start_date,
+                paused_on: reading.paused_on,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DatesJson {
+    start_date: NaiveDate,
+    /// Days between today and the reading date.
+    offset: i64,
+    reading_date: NaiveDate,
+}
+
+impl From<&UserDates> for DatesJson {
+    fn from(dates: &UserDates) -> Self {
+        Self {
+            start_date: dates.start_date,
+            offset: dates.offset,
+            reading_date: dates.reading_date(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DatesRequest {
+    start_date: NaiveDate,
+    reading_date: NaiveDate,
+}
+
+/// The start date of the plan and the date being read.
+#[utoipa::path(
+    get,
+    path = "/api/v1/dates",
+    tag = "readings",
+    responses(
+        (status = 200, body = DatesJson),
+        (status = 401, body = ErrorJson)
+    )
+)]
+pub async fn get_dates(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+) -> Result<Json<DatesJson>, V1Error> {
+    let user = logged_in(user)?;
+    let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+    Ok(Json(DatesJson::from(&dates)))
+}
+
+/// Set the start date of the plan and the date being read.
+#[utoipa::path(
+    put,
+    path = "/api/v1/dates",
+    tag = "readings",
+    request_body = DatesRequest,
+    responses(
+        (status = 200, body = DatesJson),
+        (status = 401, body = ErrorJson),
+        (status = 403, body = ErrorJson)
+    )
+)]
+pub async fn put_dates(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+    Json(request): Json<DatesRequest>,
+) -> Result<Json<DatesJson>, V1Error> {
+    let user = can_write(user)?;
+    let offset = (request.reading_date - today_naive_date()).num_days();
+    UserDates::set(&state.db, user.id, request.start_date, offset).await;
+    Ok(Json(DatesJson::from(&UserDates {
+        start_date: request.start_date,
+        offset,
+    })))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProgressJson {
+    date: NaiveDate,
+    entries: Vec<ProgressEntryJson>,
+}
+
+/// A chapter marked as read.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProgressEntryJson {
+    /// Index of the reading list.
+    list: usize,
+    book: String,
+    chapter: i64,
+}
+
+impl From<&ProgressEntry> for ProgressEntryJson {
+    fn from(entry: &ProgressEntry) -> Self {
+        Self {
+            list: entry.reading_idx,
+            book: entry.book.to_string(),
+            chapter: entry.chapter,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProgressQuery {
+    /// Defaults to the reading date.
+    date: Option<NaiveDate>,
+}
+
+/// Chapters marked as read on a date.
+#[utoipa::path(
+    get,
+    path = "/api/v1/progress",
+    tag = "progress",
+    params(ProgressQuery),
+    responses(
+        (status = 200, body = ProgressJson),
+        (status = 401, body = ErrorJson)
+    )
+)]
+pub async fn get_progress(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+    Query(query): Query<ProgressQuery>,
+) -> Result<Json<ProgressJson>, V1Error> {
+    let user = logged_in(user)?;
+    let date = match query.date {
+        Some(date) => date,
+        None => UserDates::from_user_or_set_default(&state.db, user.id)
+            .await
+            .reading_date(),
+    };
+    let progress = UserProgress::from_user(&state.db, user.id, date).await?;
+
+    Ok(Json(ProgressJson {
+        date,
+        entries: progress
+            .entries
+            .iter()
+            .map(ProgressEntryJson::from)
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReadRequest {
+    read: bool,
+    /// The `position` of the list as last seen by the client.
+    position: i64,
+}
+
+/// Mark the due portion of a reading list as read or unread on the reading
+/// date. Catch-up lists advance when marked as read and can't be unmarked.
+/// Fails with 409 when `position` is not the current one, so a retried request
+/// doesn't skip a portion of a catch-up list.
+#[utoipa::path(
+    put,
+    path = "/api/v1/readings/{index}/progress",
+    tag = "progress",
+    params(("index" = usize, Path, description = "Index of the reading list")),
+    request_body = ReadRequest,
+    responses(
+        (status = 200, body = ListPlanJson),
+        (status = 400, body = ErrorJson),
+        (status = 401, body = ErrorJson),
+        (status = 403, body = ErrorJson),
+        (status = 404, body = ErrorJson),
+        (status = 409, body = ErrorJson)
+    )
+)]
+pub async fn put_reading_progress(
+    State(state): State<AppState>,
+    user: Option<ApiUser>,
+    Path(index): Path<usize>,
+    Json(request): Json<ReadRequest>,
+) -> Result<Json<ListPlanJson>, V1Error> {
+    let user = can_write(user)?;
+    let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+    let mut readings = UserReadings::from_user(&state.db, user.id).await;
+    if index >= readings.readings.len() {
+        return Err(V1Error::NotFound(format!("No reading list {index}")));
+    }
+
+    let mut progress = UserProgress::from_user(&state.db, user.id, dates.reading_date()).await?;
+    let reading = &mut readings.readings[index];
+    if request.position != reading.position {
+        return Err(V1Error::Conflict(format!(
+            "Reading list {index} is at position {}",
+            reading.position
+        )));
+    }
+    let portion = reading.portion(&dates);
+    let is_read = progress.is_portion_read(index, &portion);
+    match (reading.catch_up, request.read) {
+        (true, true) => {
+            progress.mark_portion(&state.db, index, &portion).await?;
+            reading.position += 1;
+            readings.update(&state.db, index).await?;
+        }
+        (true, false) => {
+            return Err(V1Error::BadRequest(
+                "Catch-up lists can't be marked as unread".to_string(),
+            ))
+        }
+        (false, read) if read != is_read => {
+            progress.toggle_portion(&state.db, index, &portion).await?;
+        }
+        (_, _) => {}
+    }
+
+    Ok(Json(ListPlanJson::new(
+        index,
+        &readings.readings[index],
+        &dates,
+        &progress,
+    )))
+}
+
+pub async fn get_openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
@@ -0,0 +1,154 @@
+use super::{
+    session::{delete_user_session, delete_user_sessions, Device, SESSION_COOKIE},
+    User,
+};
+use crate::{
+    brp::model::UserSettings,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        hx::{HxCfg, HxHeaderBuilder, HxSwap},
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use axum_extra::extract::PrivateCookieJar;
+use cookie::Cookie;
+use maud::{html, Markup};
+
+/// The devices section of the settings page, listing the sessions of the user.
+pub fn fragment_devices(devices: &[Device], locale: Locale) -> Markup {
+    html! {
+        section id="devices" {
+            h2 class="font-bold text-md mb-1" { (locale.t("Devices")) }
+            p class="text-sm text-foreground/60 mb-4" {
+                (locale.t("Browsers where you're logged in. Log out any you don't recognise."))
+            }
+            table class="w-full text-sm mb-4" {
+                thead {
+                    tr class="border-b border-border text-left" {
+                        th class="py-1" { (locale.t("Device")) }
+                        th class="py-1" { (locale.t("Logged in")) }
+                        th class="py-1" { (locale.t("Last seen")) }
+                        th {}
+                    }
+                }
+                tbody {
+                    @for device in devices {
+                        tr class="border-b border-border/50" {
+                            td class="py-1" {
+                                @match device.name() {
+                                    Some(name) => (name),
+                                    None => (locale.t("Unknown device")),
+                                }
+                                @if device.current {
+                                    span class="ml-2 text-xs text-green-600" { (locale.t("This device")) }
+                                }
+                            }
+                            td class="py-1" { (device.created_at.format("%d/%m/%Y")) }
+                            td class="py-1" {
+                                @if let Some(seen) = device.last_seen_at {
+                                    (seen.format("%d/%m/%Y %H:%M"))
+                                }
+                            }
+                            td class="py-1 text-right" {
+                                @if !device.current {
+                                    button type="button" class="px-2 py-1 text-xs text-red-600 hover:bg-red-50"
+                                        hx-post=(format!("/settings/devices/{}/revoke", device.id))
+                                        hx-target="#devices"
+                                        hx-swap="outerHTML"
+                                    { (locale.t("Log out")) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div class="flex gap-2" {
+                @if devices.iter().any(|device| !device.current) {
+                    (ui_button(html! { (locale.t("Log out other devices")) },
+                        &ButtonCfg::new().with_color(Color::Alternative),
+                        &HxCfg::new()
+                            .with_post("/settings/devices/revoke")
+                            .with_target("#devices")
+                            .with_swap("outerHTML")
+                    ))
+                }
+                (ui_button(html! { (locale.t("Log out everywhere")) },
+                    &ButtonCfg::new().with_color(Color::Default),
+                    &HxCfg::new().with_post("/logout/all")
+                ))
+            }
+        }
+    }
+}
+
+async fn devices(
+    state: &AppState,
+    user: &User,
+    jar: &PrivateCookieJar,
+) -> Result<Markup, ApiError> {
+    let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+    let current = jar.get(SESSION_COOKIE);
+    let devices =
+        Device::all_from_user(&state.db, user.id, current.as_ref().map(Cookie::value)).await?;
+    Ok(fragment_devices(&devices, locale))
+}
+
+pub async fn post_revoke_device(
+    State(state): State<AppState>,
+    user: Option<User>,
+    jar: PrivateCookieJar,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            delete_user_session(&state.db, user.id, id).await?;
+            devices(&state, &user, &jar).await
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// Log out every device except the one making the request.
+pub async fn post_revoke_other_devices(
+    State(state): State<AppState>,
+    user: Option<User>,
+    jar: PrivateCookieJar,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let current = jar.get(SESSION_COOKIE);
+            delete_user_sessions(&state.db, user.id, current.as_ref().map(Cookie::value)).await?;
+            devices(&state, &user, &jar).await
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// Log out every device, this one included.
+pub async fn post_logout_all(
+    State(state): State<AppState>,
+    user: Option<User>,
+    jar: PrivateCookieJar,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(user) = user {
+        delete_user_sessions(&state.db, user.id, None).await?;
+    }
+
+    Ok((
+        jar.remove(Cookie::build(SESSION_COOKIE).path("/")),
+        HxHeaderBuilder::new()
+            .with_swap(HxSwap::None)
+            .with_redirect("/login")
+            .build(),
+    ))
+}
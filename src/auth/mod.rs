@@ -0,0 +1,281 @@
+use crate::api::tokens::{ApiToken, TokenScope};
+use crate::errors::ApiError;
+use crate::mail::MailError;
+use crate::view::hx::{HxHeaderBuilder, HxSwap};
+use crate::view::pages::login::{
+    fragment_login_error, fragment_login_link_sent, page_confirm_login_link,
+};
+use crate::AppState;
+use axum::extract::{FromRequestParts, Path, Query, State};
+use axum::http::{
+    header::{AUTHORIZATION, USER_AGENT},
+    request::Parts,
+    HeaderMap, StatusCode,
+};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Form;
+use axum_extra::extract::PrivateCookieJar;
+use cookie::{Cookie, Key};
+use lazy_static::lazy_static;
+use providers::IdentityProvider;
+use serde::Deserialize;
+use session::{create_session, delete_session, session_cookie, session_user, SESSION_COOKIE};
+use sqlx::query;
+use std::collections::HashMap;
+
+pub mod devices;
+pub mod providers;
+pub mod session;
+
+lazy_static! {
+    pub static ref JWT_SECRET: String =
+        std::env::var("JWT_SECRET").expect("JWT_SECRET env var must be set");
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Clone)]
+pub struct User {
+    pub email: String,
+    pub id: i64,
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for Option<User> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let cookiejar = PrivateCookieJar::<Key>::from_request_parts(parts, state).await?;
+        let Some(cookie) = cookiejar
+            .get(SESSION_COOKIE)
+            .map(|cookie| cookie.value().to_owned())
+        else {
+            return Ok(None);
+        };
+
+        Ok(session_user(&state.db, &cookie).await?)
+    }
+}
+
+/// A user of the JSON API, authenticated by an API token in the
+/// `Authorization: Bearer` header or else by the session cookie.
+#[derive(Debug, Clone)]
+pub struct ApiUser {
+    pub user: User,
+    /// `Write` for browser sessions.
+    pub scope: TokenScope,
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for Option<ApiUser> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(header) = parts.headers.get(AUTHORIZATION) {
+            // A malformed or unknown token doesn't fall back to the cookie.
+            let Some(token) = header
+                .to_str()
+                .ok()
+                .and_then(|header| header.strip_prefix("Bearer "))
+            else {
+                return Ok(None);
+            };
+            let user = ApiToken::authenticate(&state.db, token.trim()).await?;
+            return Ok(user.map(|(user, scope)| ApiUser { user, scope }));
+        }
+
+        let user = Option::<User>::from_request_parts(parts, state).await?;
+        Ok(user.map(|user| ApiUser {
+            user,
+            scope: TokenScope::Write,
+        }))
+    }
+}
+
+/// Users are matched by email whichever provider vouched for it, so every
+/// address is stored trimmed and lowercased.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Log in the user with the email address a provider vouched for, creating
+/// the user on the first login.
+async fn log_in(
+    state: &AppState,
+    jar: PrivateCookieJar,
+    headers: &HeaderMap,
+    email: &str,
+) -> Result<PrivateCookieJar, ApiError> {
+    let email = normalize_email(email);
+    // New users pick their reading plan on the first visit of `/`.
+    query!(
+        "INSERT INTO users (email) VALUES (?) ON CONFLICT (email) DO NOTHING",
+        email
+    )
+    .execute(&state.db)
+    .await?;
+    let user_id = query!(r#"SELECT id AS "id!" FROM users WHERE email = ?"#, email)
+        .fetch_one(&state.db)
+        .await?
+        .id;
+
+    let user_agent = headers.get(USER_AGENT).and_then(|ua| ua.to_str().ok());
+    let session = create_session(&state.db, user_id, user_agent).await?;
+    Ok(jar.add(session_cookie(session)))
+}
+
+/// Where redirecting providers send the user back to. Login links only get
+/// a page to confirm with, as mail scanners open links too and would use
+/// them up.
+pub async fn get_callback(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    if let Some(email_link) = state
+        .providers
+        .email_link()
+        .filter(|email_link| email_link.slug() == provider)
+    {
+        return Ok(match params.get("token") {
+            Some(token) => page_confirm_login_link(email_link.slug(), token).into_response(),
+            None => ApiError::LoginFailed.into_response(),
+        });
+    }
+    callback(&state, jar, &headers, &provider, &params).await
+}
+
+/// The confirmation of a login link.
+pub async fn post_callback(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    callback(&state, jar, &headers, &provider, &params).await
+}
+
+async fn callback(
+    state: &AppState,
+    jar: PrivateCookieJar,
+    headers: &HeaderMap,
+    provider: &str,
+    params: &HashMap<String, String>,
+) -> Result<Response, ApiError> {
+    let Some(provider) = state.providers.get(provider) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let email = provider.authenticate(&state.db, &jar, params).await;
+    let jar = provider.finish(jar);
+    match email? {
+        Some(email) => {
+            let jar = log_in(state, jar, headers, &email).await?;
+            Ok((jar, Redirect::to("/")).into_response())
+        }
+        None => Ok((jar, ApiError::LoginFailed).into_response()),
+    }
+}
+
+/// The callback URL registered with Google before there were other providers.
+pub async fn google_callback(
+    state: State<AppState>,
+    jar: PrivateCookieJar,
+    headers: HeaderMap,
+    params: Query<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    get_callback(state, jar, headers, Path("google".to_string()), params).await
+}
+
+/// The credential form of providers without a redirect.
+pub async fn post_login(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    let Some(provider) = state.providers.get(&provider) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let email = provider.authenticate(&state.db, &jar, &params).await;
+    let jar = provider.finish(jar);
+    match email? {
+        Some(email) => {
+            let jar = log_in(&state, jar, &headers, &email).await?;
+            Ok((
+                jar,
+                HxHeaderBuilder::new()
+                    .with_swap(HxSwap::None)
+                    .with_redirect("/")
+                    .build(),
+            )
+                .into_response())
+        }
+        None => Ok((
+            jar,
+            fragment_login_error(provider.slug(), "Wrong email or password"),
+        )
+            .into_response()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginLinkRequest {
+    email: String,
+}
+
+/// Mail a login link, for providers offering `LoginMethod::EmailLink`.
+pub async fn post_login_link(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Form(form): Form<LoginLinkRequest>,
+) -> Result<Response, ApiError> {
+    let Some(email_link) = state
+        .providers
+        .email_link()
+        .filter(|email_link| email_link.slug() == provider)
+    else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let email = normalize_email(&form.email);
+    if email.len() > 255 || !email.contains('@') {
+        return Ok(fragment_login_error(&provider, "Enter a valid email address").into_response());
+    }
+    match email_link.send_link(&state.db, &email).await {
+        Ok(true) => Ok(fragment_login_link_sent(&provider, &email).into_response()),
+        Ok(false) => Ok(fragment_login_error(
+            &provider,
+            "Several links were sent to this address already, use one of those or try again later",
+        )
+        .into_response()),
+        Err(ApiError::Mail(MailError::Address(_))) => {
+            Ok(fragment_login_error(&provider, "Enter a valid email address").into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn post_logout(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+) -> impl IntoResponse {
+    if let Some(cookie) = jar.get(SESSION_COOKIE) {
+        delete_session(&state.db, cookie.value()).await.unwrap();
+    }
+
+    (
+        jar.remove(Cookie::build(SESSION_COOKIE).path("/")),
+        HxHeaderBuilder::new()
+            .with_swap(HxSwap::None)
+            .with_redirect("/login")
+            .build(),
+    )
+}
@@ -0,0 +1,150 @@
+use super::{IdentityProvider, LoginMethod};
+use crate::{
+    errors::ApiError,
+    mail::{Mail, MailTransport},
+};
+use axum_extra::extract::PrivateCookieJar;
+use chrono::{Duration, NaiveDateTime, Utc};
+use rand::RngCore;
+use sqlx::SqlitePool;
+use std::{collections::HashMap, sync::Arc};
+
+/// How long a login link works.
+pub const LINK_MINUTES: i64 = 15;
+
+/// Unused links an address may have at once, so the form can't be used to
+/// flood someone's inbox.
+const MAX_OPEN_LINKS: i64 = 3;
+
+fn hash_token(token: &str) -> String {
+    sha256::digest(token)
+}
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// Passwordless login with a single-use link sent by email.
+pub struct EmailLinkProvider {
+    mailer: Arc<dyn MailTransport>,
+    /// Where the server is reachable, to build the links.
+    base_url: String,
+}
+
+impl EmailLinkProvider {
+    pub fn new(mailer: Arc<dyn MailTransport>, base_url: &str) -> Self {
+        Self {
+            mailer,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The provider if `EMAIL_LOGIN` is set to `true`, which needs a
+    /// configured `MAIL_TRANSPORT`.
+    pub fn from_env(mailer: Option<Arc<dyn MailTransport>>) -> Option<Self> {
+        let enabled =
+            std::env::var("EMAIL_LOGIN").is_ok_and(|enabled| enabled == "true" || enabled == "1");
+        if !enabled {
+            return None;
+        }
+        let mailer = mailer.expect("MAIL_TRANSPORT env var must be set for EMAIL_LOGIN");
+        let base_url = std::env::var("BASE_URL").expect("BASE_URL env var must be set");
+        Some(Self::new(mailer, &base_url))
+    }
+
+    /// Mail a login link to the address. Returns `false` without sending if
+    /// the address has too many unused links. A link that couldn't be sent is
+    /// forgotten again, so it doesn't count as unused.
+    pub async fn send_link(&self, db: &SqlitePool, email: &str) -> Result<bool, ApiError> {
+        let now = now();
+        sqlx::query!("DELETE FROM login_links WHERE expires_at <= ?", now)
+            .execute(db)
+            .await?;
+        let open = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM login_links WHERE email = ?"#,
+            email
+        )
+        .fetch_one(db)
+        .await?
+        .count;
+        if open >= MAX_OPEN_LINKS {
+            return Ok(false);
+        }
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let hash = hash_token(&token);
+        let expires_at = now + Duration::minutes(LINK_MINUTES);
+        sqlx::query!(
+            "INSERT INTO login_links (email, token_hash, expires_at) VALUES (?1, ?2, ?3)",
+            email,
+            hash,
+            expires_at
+        )
+        .execute(db)
+        .await?;
+
+        let link = format!(
+            "{}/api/auth/{}/callback?token={token}",
+            self.base_url,
+            self.slug()
+        );
+        let sent = self
+            .mailer
+            .send(&Mail {
+                to: email.to_string(),
+                subject: "Your login link".to_string(),
+                body: format!(
+                    "Open this link to log in to the Bible reading plan:\n\n{link}\n\n\
+                    It works once, within {LINK_MINUTES} minutes. If you didn't ask to log in, \
+                    you can ignore this email."
+                ),
+            })
+            .await;
+        if let Err(e) = sent {
+            sqlx::query!("DELETE FROM login_links WHERE token_hash = ?", hash)
+                .execute(db)
+                .await?;
+            return Err(e.into());
+        }
+        Ok(true)
+    }
+}
+
+#[axum::async_trait]
+impl IdentityProvider for EmailLinkProvider {
+    fn slug(&self) -> &str {
+        "email"
+    }
+
+    fn name(&self) -> &str {
+        "Email link"
+    }
+
+    fn login(&self, jar: PrivateCookieJar) -> (PrivateCookieJar, LoginMethod) {
+        (jar, LoginMethod::EmailLink)
+    }
+
+    /// Use up the link, so it can't log in anyone else.
+    async fn authenticate(
+        &self,
+        db: &SqlitePool,
+        _jar: &PrivateCookieJar,
+        params: &HashMap<String, String>,
+    ) -> Result<Option<String>, ApiError> {
+        let Some(token) = params.get("token") else {
+            return Ok(None);
+        };
+        let hash = hash_token(token);
+        let now = now();
+        Ok(sqlx::query!(
+            "DELETE FROM login_links WHERE token_hash = ?1 AND expires_at > ?2 RETURNING email",
+            hash,
+            now
+        )
+        .fetch_optional(db)
+        .await?
+        .map(|rec| rec.email))
+    }
+}
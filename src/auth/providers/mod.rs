@@ -0,0 +1,113 @@
+//! Ways to log in. Every provider vouches for an email address, which is
+//! matched to (or creates) a user.
+use crate::{errors::ApiError, mail::MailTransport};
+use axum_extra::extract::PrivateCookieJar;
+use sqlx::SqlitePool;
+use std::{collections::HashMap, sync::Arc};
+
+pub mod email;
+pub mod oauth;
+pub mod password;
+
+pub use email::EmailLinkProvider;
+pub use oauth::OAuthProvider;
+pub use password::PasswordProvider;
+
+/// How the login page offers a provider.
+pub enum LoginMethod {
+    /// A button leading to the provider.
+    Redirect(String),
+    /// An email and password form, posted to `/api/auth/{slug}/login`.
+    Credentials,
+    /// An email form, posted to `/api/auth/{slug}/link` to mail a login link.
+    EmailLink,
+}
+
+#[axum::async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Identifies the provider in URLs, e.g. `/api/auth/{slug}/callback`.
+    fn slug(&self) -> &str;
+
+    /// Shown on the login page.
+    fn name(&self) -> &str;
+
+    fn icon(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Start a login. Redirecting providers keep what the callback checks in
+    /// the jar.
+    fn login(&self, jar: PrivateCookieJar) -> (PrivateCookieJar, LoginMethod);
+
+    /// The verified email address of the user, from the callback query or the
+    /// posted form. `None` rejects the login.
+    async fn authenticate(
+        &self,
+        db: &SqlitePool,
+        jar: &PrivateCookieJar,
+        params: &HashMap<String, String>,
+    ) -> Result<Option<String>, ApiError>;
+
+    /// Clean up after `authenticate`, whether it succeeded or not.
+    fn finish(&self, jar: PrivateCookieJar) -> PrivateCookieJar {
+        jar
+    }
+}
+
+/// The providers configured for this instance, in the order of the login page.
+#[derive(Clone, Default)]
+pub struct IdentityProviders {
+    providers: Vec<Arc<dyn IdentityProvider>>,
+    /// Also in `providers`, kept apart for sending links.
+    email_link: Option<Arc<EmailLinkProvider>>,
+}
+
+impl IdentityProviders {
+    /// Providers configured by environment variables, see `.env.example`.
+    pub async fn from_env(mailer: Option<Arc<dyn MailTransport>>) -> Self {
+        let mut providers = Self::default();
+        if let Some(google) = OAuthProvider::google_from_env() {
+            providers.add(google);
+        }
+        match OAuthProvider::oidc_from_env().await {
+            Ok(Some(oidc)) => providers.add(oidc),
+            Ok(None) => {}
+            Err(e) => tracing::error!("OIDC provider not available: {e}"),
+        }
+        if PasswordProvider::enabled_from_env() {
+            providers.add(PasswordProvider);
+        }
+        if let Some(email_link) = EmailLinkProvider::from_env(mailer) {
+            providers.set_email_link(email_link);
+        }
+        if providers.providers.is_empty() {
+            tracing::warn!("no login providers configured, nobody can log in");
+        }
+        providers
+    }
+
+    pub fn add(&mut self, provider: impl IdentityProvider + 'static) {
+        self.providers.push(Arc::new(provider));
+    }
+
+    pub fn set_email_link(&mut self, provider: EmailLinkProvider) {
+        let provider = Arc::new(provider);
+        self.providers.push(provider.clone());
+        self.email_link = Some(provider);
+    }
+
+    pub fn email_link(&self) -> Option<&EmailLinkProvider> {
+        self.email_link.as_deref()
+    }
+
+    pub fn get(&self, slug: &str) -> Option<&dyn IdentityProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.slug() == slug)
+            .map(|provider| provider.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn IdentityProvider> {
+        self.providers.iter().map(|provider| provider.as_ref())
+    }
+}
@@ -0,0 +1,271 @@
+use super::{IdentityProvider, LoginMethod};
+use crate::errors::ApiError;
+use axum_extra::extract::PrivateCookieJar;
+use cookie::{Cookie, SameSite};
+use oauth2::{
+    basic::BasicClient, reqwest::async_http_client, url, AuthUrl, AuthorizationCode, ClientId,
+    ClientSecret, CsrfToken, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope,
+    TokenResponse, TokenUrl,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// How long the login page may be open before the callback.
+const OAUTH_COOKIE_MINUTES: i64 = 10;
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("fetching the discovery document: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("invalid endpoint URL: {0}")]
+    Url(#[from] url::ParseError),
+}
+
+/// The parts of an OpenID Connect discovery document used here.
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    email: String,
+    email_verified: Option<bool>,
+}
+
+/// An OAuth 2 authorization code login with PKCE, reading the email address
+/// from the userinfo endpoint of the provider.
+pub struct OAuthProvider {
+    slug: String,
+    name: String,
+    icon: Option<&'static str>,
+    client: BasicClient,
+    scopes: Vec<String>,
+    userinfo_url: String,
+    /// Accept profiles without `email_verified`, off unless configured.
+    trust_unverified_email: bool,
+}
+
+impl OAuthProvider {
+    pub fn new(
+        slug: &str,
+        name: &str,
+        client: BasicClient,
+        scope: &str,
+        userinfo_url: &str,
+    ) -> Self {
+        Self {
+            slug: slug.to_string(),
+            name: name.to_string(),
+            icon: None,
+            client,
+            scopes: scope.split_whitespace().map(str::to_string).collect(),
+            userinfo_url: userinfo_url.to_string(),
+            trust_unverified_email: false,
+        }
+    }
+
+    pub fn with_icon(mut self, icon: &'static str) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Log in with any email the provider returns, verified or not. Anyone
+    /// able to claim an address there could take over that user here.
+    pub fn trusting_unverified_email(mut self) -> Self {
+        self.trust_unverified_email = true;
+        self
+    }
+
+    /// Google, if `GOOGLE_OAUTH_CLIENT_ID` is set.
+    pub fn google_from_env() -> Option<Self> {
+        let client_id = std::env::var("GOOGLE_OAUTH_CLIENT_ID").ok()?;
+        let client_secret = std::env::var("GOOGLE_OAUTH_CLIENT_SECRET")
+            .expect("GOOGLE_OAUTH_CLIENT_SECRET env var must be set");
+        let redirect_url = std::env::var("GOOGLE_OAUTH_REDIRECT_URL")
+            .expect("GOOGLE_OAUTH_REDIRECT_URL env var must be set");
+        let scope = std::env::var("GOOGLE_OAUTH_SCOPE").unwrap_or_else(|_| "email".to_string());
+
+        let client = BasicClient::new(
+            ClientId::new(client_id),
+            Some(ClientSecret::new(client_secret)),
+            AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string())
+                .expect("Invalid authorization endpoint URL"),
+            Some(
+                TokenUrl::new("https://oauth2.googleapis.com/token".to_string())
+                    .expect("Invalid token endpoint URL"),
+            ),
+        )
+        .set_redirect_uri(RedirectUrl::new(redirect_url).expect("Invalid redirect URL"));
+
+        Some(
+            Self::new(
+                "google",
+                "Google",
+                client,
+                &scope,
+                "https://openidconnect.googleapis.com/v1/userinfo",
+            )
+            .with_icon("/static/img/icons8-google.svg"),
+        )
+    }
+
+    /// Any OpenID Connect issuer, if `OIDC_DISCOVERY_URL` is set. Its
+    /// endpoints are read from the discovery document.
+    pub async fn oidc_from_env() -> Result<Option<Self>, DiscoveryError> {
+        let Ok(discovery_url) = std::env::var("OIDC_DISCOVERY_URL") else {
+            return Ok(None);
+        };
+        let client_id =
+            std::env::var("OIDC_CLIENT_ID").expect("OIDC_CLIENT_ID env var must be set");
+        let client_secret = std::env::var("OIDC_CLIENT_SECRET").ok();
+        let redirect_url =
+            std::env::var("OIDC_REDIRECT_URL").expect("OIDC_REDIRECT_URL env var must be set");
+        let name = std::env::var("OIDC_NAME").unwrap_or_else(|_| "Single sign-on".to_string());
+        let scope = std::env::var("OIDC_SCOPE").unwrap_or_else(|_| "openid email".to_string());
+        let trust_unverified_email = std::env::var("OIDC_TRUST_UNVERIFIED_EMAIL")
+            .is_ok_and(|trust| trust == "true" || trust == "1");
+
+        let discovery = reqwest::get(&discovery_url)
+            .await?
+            .error_for_status()?
+            .json::<Discovery>()
+            .await?;
+        let client = BasicClient::new(
+            ClientId::new(client_id),
+            client_secret.map(ClientSecret::new),
+            AuthUrl::new(discovery.authorization_endpoint)?,
+            Some(TokenUrl::new(discovery.token_endpoint)?),
+        )
+        .set_redirect_uri(RedirectUrl::new(redirect_url)?);
+
+        let provider = Self::new("oidc", &name, client, &scope, &discovery.userinfo_endpoint);
+        Ok(Some(if trust_unverified_email {
+            provider.trusting_unverified_email()
+        } else {
+            provider
+        }))
+    }
+
+    /// Cookies holding the CSRF state and PKCE verifier between the login page
+    /// and the callback, one pair per provider.
+    fn state_cookie(&self) -> String {
+        format!("oauth_state_{}", self.slug)
+    }
+
+    fn verifier_cookie(&self) -> String {
+        format!("oauth_verifier_{}", self.slug)
+    }
+}
+
+fn oauth_cookie(name: String, value: String) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path("/api/auth")
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::minutes(OAUTH_COOKIE_MINUTES))
+        .build()
+}
+
+#[axum::async_trait]
+impl IdentityProvider for OAuthProvider {
+    fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn icon(&self) -> Option<&'static str> {
+        self.icon
+    }
+
+    /// The consent URL with a new CSRF state and PKCE challenge.
+    fn login(&self, jar: PrivateCookieJar) -> (PrivateCookieJar, LoginMethod) {
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        let (url, state) = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(self.scopes.iter().cloned().map(Scope::new))
+            .set_pkce_challenge(challenge)
+            .url();
+
+        let jar = jar
+            .add(oauth_cookie(self.state_cookie(), state.secret().to_owned()))
+            .add(oauth_cookie(
+                self.verifier_cookie(),
+                verifier.secret().to_owned(),
+            ));
+        (jar, LoginMethod::Redirect(url.to_string()))
+    }
+
+    /// The provider redirects back with either a code and the state, or an
+    /// error, e.g. when consent was denied.
+    async fn authenticate(
+        &self,
+        _db: &SqlitePool,
+        jar: &PrivateCookieJar,
+        params: &HashMap<String, String>,
+    ) -> Result<Option<String>, ApiError> {
+        if let Some(error) = params.get("error") {
+            tracing::debug!("{} login failed: {error}", self.slug);
+            return Ok(None);
+        }
+        let expected_state = jar.get(&self.state_cookie());
+        let verifier = jar.get(&self.verifier_cookie());
+        let (Some(code), Some(state), Some(expected_state), Some(verifier)) = (
+            params.get("code"),
+            params.get("state"),
+            expected_state,
+            verifier,
+        ) else {
+            return Ok(None);
+        };
+        if state != expected_state.value() {
+            tracing::warn!("{} login with mismatching state", self.slug);
+            return Ok(None);
+        }
+
+        let token = self
+            .client
+            .exchange_code(AuthorizationCode::new(code.to_owned()))
+            .set_pkce_verifier(PkceCodeVerifier::new(verifier.value().to_owned()))
+            .request_async(async_http_client)
+            .await;
+        // Also fails for a wrong PKCE verifier or a code used before.
+        let token = match token {
+            Ok(token) => token,
+            Err(e) => {
+                tracing::warn!("{} code exchange failed: {e}", self.slug);
+                return Ok(None);
+            }
+        };
+
+        // The token is only needed for the profile and is not kept.
+        let profile = reqwest::Client::new()
+            .get(&self.userinfo_url)
+            .bearer_auth(token.access_token().secret())
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<UserInfo>()
+            .await?;
+        if profile.email_verified != Some(true) && !self.trust_unverified_email {
+            tracing::warn!("{} login with unverified email", self.slug);
+            return Ok(None);
+        }
+        Ok(Some(profile.email))
+    }
+
+    /// The state and verifier are only good for one callback.
+    fn finish(&self, jar: PrivateCookieJar) -> PrivateCookieJar {
+        jar.remove(Cookie::build(self.state_cookie()).path("/api/auth"))
+            .remove(Cookie::build(self.verifier_cookie()).path("/api/auth"))
+    }
+}
@@ -0,0 +1,102 @@
+use super::{IdentityProvider, LoginMethod};
+use crate::{auth::normalize_email, errors::ApiError};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum_extra::extract::PrivateCookieJar;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// Shortest password `set-password` accepts.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Local accounts with an Argon2 password hash, set with the `set-password`
+/// binary.
+pub struct PasswordProvider;
+
+impl PasswordProvider {
+    /// Whether `LOCAL_LOGIN` is set to `true`.
+    pub fn enabled_from_env() -> bool {
+        std::env::var("LOCAL_LOGIN").is_ok_and(|enabled| enabled == "true" || enabled == "1")
+    }
+}
+
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("hashing with a generated salt")
+        .to_string()
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(hash) else {
+        return false;
+    };
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+/// Set the password of the user with the email address, creating the user if
+/// there's none yet.
+pub async fn set_password(
+    pool: &SqlitePool,
+    email: &str,
+    password: &str,
+) -> Result<(), sqlx::Error> {
+    let email = normalize_email(email);
+    let hash = hash_password(password);
+    sqlx::query!(
+        "INSERT INTO users (email, password_hash) VALUES (?1, ?2)
+        ON CONFLICT (email) DO UPDATE
+        SET password_hash = excluded.password_hash, last_updated = CURRENT_TIMESTAMP",
+        email,
+        hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[axum::async_trait]
+impl IdentityProvider for PasswordProvider {
+    fn slug(&self) -> &str {
+        "local"
+    }
+
+    fn name(&self) -> &str {
+        "Email and password"
+    }
+
+    fn login(&self, jar: PrivateCookieJar) -> (PrivateCookieJar, LoginMethod) {
+        (jar, LoginMethod::Credentials)
+    }
+
+    async fn authenticate(
+        &self,
+        db: &SqlitePool,
+        _jar: &PrivateCookieJar,
+        params: &HashMap<String, String>,
+    ) -> Result<Option<String>, ApiError> {
+        let (Some(email), Some(password)) = (params.get("email"), params.get("password")) else {
+            return Ok(None);
+        };
+        let email = normalize_email(email);
+        let hash = sqlx::query!("SELECT password_hash FROM users WHERE email = ?", email)
+            .fetch_optional(db)
+            .await?
+            .and_then(|rec| rec.password_hash);
+        let Some(hash) = hash else {
+            return Ok(None);
+        };
+
+        // Argon2 is slow on purpose, keep it off the async workers.
+        let password = password.to_owned();
+        let valid = tokio::task::spawn_blocking(move || verify_password(&password, &hash))
+            .await
+            .unwrap_or(false);
+        Ok(valid.then_some(email))
+    }
+}
@@ -0,0 +1,257 @@
+use super::User;
+use chrono::{Duration, NaiveDateTime, Utc};
+use cookie::{Cookie, SameSite};
+use rand::RngCore;
+use sqlx::SqlitePool;
+
+/// Name of the cookie holding the session ID.
+pub const SESSION_COOKIE: &str = "sid";
+
+/// Lifetime of a session, see `session_user` for its renewal.
+const SESSION_DAYS: i64 = 30;
+
+/// How stale `last_seen_at` may get before a request updates it, to not write
+/// on every request.
+const LAST_SEEN_INTERVAL: i64 = 5;
+
+/// How often expired sessions are deleted.
+const SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+fn hash_session_id(id: &str) -> String {
+    sha256::digest(id)
+}
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// A session of the user, as listed on the settings page.
+#[derive(Debug)]
+pub struct Device {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: Option<NaiveDateTime>,
+    /// Whether this is the session of the request.
+    pub current: bool,
+}
+
+impl Device {
+    /// Unexpired sessions of the user, most recently seen first. `current` is
+    /// the session ID of the request.
+    pub async fn all_from_user(
+        pool: &SqlitePool,
+        user_id: i64,
+        current: Option<&str>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let hash = current.map(hash_session_id);
+        let now = now();
+        Ok(sqlx::query!(
+            r#"SELECT id AS "id!", user_agent,
+                created_at AS "created_at!: NaiveDateTime",
+                last_seen_at AS "last_seen_at: NaiveDateTime",
+                session_hash IS ?2 AS "current!: bool"
+            FROM sessions
+            WHERE user_id = ?1 AND expires_at > ?3
+            ORDER BY COALESCE(last_seen_at, created_at) DESC"#,
+            user_id,
+            hash,
+            now
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|rec| Self {
+            id: rec.id,
+            user_agent: rec.user_agent,
+            created_at: rec.created_at,
+            last_seen_at: rec.last_seen_at,
+            current: rec.current,
+        })
+        .collect())
+    }
+
+    /// Browser and operating system, guessed from the user agent.
+    pub fn name(&self) -> Option<String> {
+        let ua = self.user_agent.as_deref()?;
+        // Order matters, e.g. Edge and Chrome also claim to be Safari.
+        let browser = [
+            ("Firefox/", "Firefox"),
+            ("Edg/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+        ]
+        .into_iter()
+        .find(|(token, _)| ua.contains(token))
+        .map(|(_, name)| name);
+        let os = [
+            ("Android", "Android"),
+            ("iPhone", "iOS"),
+            ("iPad", "iPadOS"),
+            ("Windows", "Windows"),
+            ("Mac OS X", "macOS"),
+            ("CrOS", "ChromeOS"),
+            ("Linux", "Linux"),
+        ]
+        .into_iter()
+        .find(|(token, _)| ua.contains(token))
+        .map(|(_, name)| name);
+
+        match (browser, os) {
+            (Some(browser), Some(os)) => Some(format!("{browser}, {os}")),
+            (Some(name), None) | (None, Some(name)) => Some(name.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Start a session for the user and return its ID, to be handed out in the
+/// session cookie. Only its hash is stored.
+pub async fn create_session(
+    pool: &SqlitePool,
+    user_id: i64,
+    user_agent: Option<&str>,
+) -> Result<String, sqlx::Error> {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let id = hex::encode(bytes);
+
+    let hash = hash_session_id(&id);
+    let expires_at = now() + Duration::days(SESSION_DAYS);
+    sqlx::query!(
+        "INSERT INTO sessions (user_id, session_hash, user_agent, last_seen_at, expires_at)
+        VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP, ?4)",
+        user_id,
+        hash,
+        user_agent,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// The user of an unexpired session. Sessions used in the second half of their
+/// lifetime are renewed, so active users stay logged in, and `last_seen_at` is
+/// kept up to date within `LAST_SEEN_INTERVAL` minutes.
+pub async fn session_user(pool: &SqlitePool, id: &str) -> Result<Option<User>, sqlx::Error> {
+    let hash = hash_session_id(id);
+    let now = now();
+    let rec = sqlx::query!(
+        r#"SELECT sessions.id AS "session_id!", sessions.expires_at AS "expires_at: NaiveDateTime",
+            sessions.last_seen_at AS "last_seen_at: NaiveDateTime", users.id AS "user_id!", users.email
+        FROM sessions
+        JOIN users ON sessions.user_id = users.id
+        WHERE sessions.session_hash = ?1 AND sessions.expires_at > ?2"#,
+        hash,
+        now
+    )
+    .fetch_optional(pool)
+    .await?;
+    let Some(rec) = rec else {
+        return Ok(None);
+    };
+
+    let renew = rec.expires_at - now < Duration::days(SESSION_DAYS / 2);
+    let stale = rec
+        .last_seen_at
+        .is_none_or(|seen| now - seen >= Duration::minutes(LAST_SEEN_INTERVAL));
+    if renew || stale {
+        let expires_at = if renew {
+            now + Duration::days(SESSION_DAYS)
+        } else {
+            rec.expires_at
+        };
+        sqlx::query!(
+            "UPDATE sessions SET expires_at = ?1, last_seen_at = ?2 WHERE id = ?3",
+            expires_at,
+            now,
+            rec.session_id
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(Some(User {
+        email: rec.email,
+        id: rec.user_id,
+    }))
+}
+
+pub async fn delete_session(pool: &SqlitePool, id: &str) -> Result<(), sqlx::Error> {
+    let hash = hash_session_id(id);
+    sqlx::query!("DELETE FROM sessions WHERE session_hash = ?", hash)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete a session of the user, logging out that device.
+pub async fn delete_user_session(
+    pool: &SqlitePool,
+    user_id: i64,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM sessions WHERE id = ? AND user_id = ?",
+        id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete all sessions of the user, except the session `keep` if given.
+pub async fn delete_user_sessions(
+    pool: &SqlitePool,
+    user_id: i64,
+    keep: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let hash = keep.map(hash_session_id);
+    sqlx::query!(
+        "DELETE FROM sessions WHERE user_id = ?1 AND session_hash IS NOT ?2",
+        user_id,
+        hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete expired sessions, returning how many there were.
+pub async fn sweep_expired_sessions(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let now = now();
+    let res = sqlx::query!("DELETE FROM sessions WHERE expires_at <= ?", now)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+/// Periodically run `sweep_expired_sessions` for as long as the server runs.
+pub fn spawn_session_sweeper(pool: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match sweep_expired_sessions(&pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!("deleted {n} expired sessions"),
+                Err(e) => tracing::error!("sweeping sessions: {e}"),
+            }
+        }
+    });
+}
+
+/// The session cookie. The browser may keep it longer than the session lasts,
+/// expiry is enforced (and extended) on the server.
+pub fn session_cookie(id: String) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, id))
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::days(400))
+        .build()
+}
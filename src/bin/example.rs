@@ -88,7 +88,7 @@ async fn user_reading_example(pool: &SqlitePool) {
             vec![Book::John],
         ],
     );
-    user_reading.replace_current(&pool).await;
+    user_reading.replace_current(&pool).await.unwrap();
 
     let user_readings = UserReadings::from_user(&pool, 1).await;
     println!("{:#?}", user_readings);
@@ -0,0 +1,69 @@
+//! Load a translation into the local verse store.
+//!
+//! cargo run --bin import-bible -- <osis|usfm|zefania> <code> <name> <file>...
+//!
+//! USFM translations come as one file per book, so pass all of them at once.
+//! Importing a code again replaces its text.
+use brp_web::{
+    brp::import::{self, Format},
+    utils::open_database,
+};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: import-bible <osis|usfm|zefania> <code> <name> <file>...";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [format, code, name, files @ ..] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if files.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+    let format = match format.parse::<Format>() {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut verses = Vec::new();
+    for file in files {
+        let input = match std::fs::read_to_string(file) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("can't read {file}: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        match import::parse(format, &input) {
+            Ok(parsed) => {
+                println!("{file}: {} verses", parsed.len());
+                verses.extend(parsed);
+            }
+            Err(e) => {
+                eprintln!("can't parse {file}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    dotenv::dotenv().ok();
+    let pool = match open_database().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = import::store_translation(&pool, code, name, &verses).await {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+    println!("stored {} verses as {code} ({name})", verses.len());
+    ExitCode::SUCCESS
+}
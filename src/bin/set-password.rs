@@ -0,0 +1,53 @@
+//! Create a local account, or change its password, for the email and
+//! password login (`LOCAL_LOGIN=true`).
+//!
+//! cargo run --bin set-password -- <email>
+//!
+//! The password is read from standard input.
+use brp_web::{
+    auth::providers::password::{set_password, MIN_PASSWORD_LENGTH},
+    utils::open_database,
+};
+use std::{io::BufRead, process::ExitCode};
+
+const USAGE: &str = "usage: set-password <email> < password";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [email] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let email = email.trim();
+    if !email.contains('@') {
+        eprintln!("not an email address: {email}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut password = String::new();
+    if let Err(e) = std::io::stdin().lock().read_line(&mut password) {
+        eprintln!("can't read the password: {e}");
+        return ExitCode::FAILURE;
+    }
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        eprintln!("the password needs at least {MIN_PASSWORD_LENGTH} characters");
+        return ExitCode::FAILURE;
+    }
+
+    dotenv::dotenv().ok();
+    let pool = match open_database().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = set_password(&pool, email, password).await {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+    println!("password set for {email}");
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,712 @@
+use super::{
+    books::{Book, Passage, MAX_VERSES},
+    chapter_view, fragment_chapter,
+    model::UserSettings,
+    translations,
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        self,
+        hx::HxCfg,
+        markdown::markdown,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Form,
+};
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightColor {
+    Yellow,
+    Green,
+    Blue,
+    Pink,
+}
+
+impl HighlightColor {
+    pub fn all() -> [HighlightColor; 4] {
+        [
+            HighlightColor::Yellow,
+            HighlightColor::Green,
+            HighlightColor::Blue,
+            HighlightColor::Pink,
+        ]
+    }
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            HighlightColor::Yellow => "yellow",
+            HighlightColor::Green => "green",
+            HighlightColor::Blue => "blue",
+            HighlightColor::Pink => "pink",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightColor::Yellow => "Yellow",
+            HighlightColor::Green => "Green",
+            HighlightColor::Blue => "Blue",
+            HighlightColor::Pink => "Pink",
+        }
+    }
+
+    /// Background of highlighted verses.
+    pub fn class(&self) -> &'static str {
+        match self {
+            HighlightColor::Yellow => "bg-yellow-200/60",
+            HighlightColor::Green => "bg-green-200/60",
+            HighlightColor::Blue => "bg-blue-200/60",
+            HighlightColor::Pink => "bg-pink-200/60",
+        }
+    }
+
+    /// Colour picker button.
+    pub fn swatch(&self) -> &'static str {
+        match self {
+            HighlightColor::Yellow => "bg-yellow-300",
+            HighlightColor::Green => "bg-green-300",
+            HighlightColor::Blue => "bg-blue-300",
+            HighlightColor::Pink => "bg-pink-300",
+        }
+    }
+}
+
+impl Display for HighlightColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.slug())
+    }
+}
+
+impl FromStr for HighlightColor {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HighlightColor::all()
+            .into_iter()
+            .find(|color| color.slug() == s)
+            .ok_or("unknown highlight color")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub book: Book,
+    pub chapter: i64,
+    pub verse: u32,
+    pub color: HighlightColor,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub book: Book,
+    pub chapter: i64,
+    pub verse: u32,
+    pub end_verse: u32,
+}
+
+impl Bookmark {
+    pub fn passage(&self) -> Passage {
+        Passage::verses(self.book.clone(), self.chapter, self.verse, self.end_verse)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub id: i64,
+    pub book: Book,
+    pub chapter: i64,
+    pub verse: u32,
+    pub end_verse: u32,
+    /// Markdown.
+    pub body: String,
+}
+
+impl Note {
+    pub fn passage(&self) -> Passage {
+        Passage::verses(self.book.clone(), self.chapter, self.verse, self.end_verse)
+    }
+}
+
+/// Highlights, bookmarks and notes of a user, either all of them or those of
+/// the chapters on screen.
+#[derive(Debug, Default)]
+pub struct Annotations {
+    pub highlights: Vec<Highlight>,
+    /// Most recent first.
+    pub bookmarks: Vec<Bookmark>,
+    /// Most recently edited first.
+    pub notes: Vec<Note>,
+}
+
+impl Annotations {
+    pub async fn from_user(pool: &SqlitePool, user_id: i64) -> Result<Self, sqlx::Error> {
+        Self::load(pool, user_id, None).await
+    }
+
+    pub async fn for_chapter(
+        pool: &SqlitePool,
+        user_id: i64,
+        book: &Book,
+        chapter: i64,
+    ) -> Result<Self, sqlx::Error> {
+        Self::load(pool, user_id, Some((book, chapter))).await
+    }
+
+    /// Annotations of every chapter the portion touches.
+    pub async fn for_portion(
+        pool: &SqlitePool,
+        user_id: i64,
+        portion: &[Passage],
+    ) -> Result<Self, sqlx::Error> {
+        let mut annotations = Self::default();
+        for passage in portion {
+            for chapter in passage.chapters() {
+                let more = Self::for_chapter(pool, user_id, &passage.book, chapter).await?;
+                annotations.highlights.extend(more.highlights);
+                annotations.bookmarks.extend(more.bookmarks);
+                annotations.notes.extend(more.notes);
+            }
+        }
+        Ok(annotations)
+    }
+
+    async fn load(
+        pool: &SqlitePool,
+        user_id: i64,
+        chapter: Option<(&Book, i64)>,
+    ) -> Result<Self, sqlx::Error> {
+        let book = chapter.map(|(book, _)| book.index() as i64);
+        let chapter = chapter.map(|(_, chapter)| chapter);
+
+        let highlights = sqlx::query!(
+            "SELECT book, chapter, verse, color FROM highlights
+            WHERE user_id = ?1 AND (?2 IS NULL OR book = ?2) AND (?3 IS NULL OR chapter = ?3)
+            ORDER BY book, chapter, verse",
+            user_id,
+            book,
+            chapter
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rec| {
+            Some(Highlight {
+                book: Book::from_index(rec.book as usize)?,
+                chapter: rec.chapter,
+                verse: rec.verse as u32,
+                color: rec.color.parse().ok()?,
+            })
+        })
+        .collect();
+
+        let bookmarks = sqlx::query!(
+            "SELECT book, chapter, verse, end_verse FROM bookmarks
+            WHERE user_id = ?1 AND (?2 IS NULL OR book = ?2) AND (?3 IS NULL OR chapter = ?3)
+            ORDER BY created_at DESC",
+            user_id,
+            book,
+            chapter
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rec| {
+            Some(Bookmark {
+                book: Book::from_index(rec.book as usize)?,
+                chapter: rec.chapter,
+                verse: rec.verse as u32,
+                end_verse: rec.end_verse as u32,
+            })
+        })
+        .collect();
+
+        let notes = sqlx::query!(
+            r#"SELECT id AS "id!", book, chapter, verse, end_verse, body FROM notes
+            WHERE user_id = ?1 AND (?2 IS NULL OR book = ?2) AND (?3 IS NULL OR chapter = ?3)
+            ORDER BY updated_at DESC, id DESC"#,
+            user_id,
+            book,
+            chapter
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rec| {
+            Some(Note {
+                id: rec.id,
+                book: Book::from_index(rec.book as usize)?,
+                chapter: rec.chapter,
+                verse: rec.verse as u32,
+                end_verse: rec.end_verse as u32,
+                body: rec.body,
+            })
+        })
+        .collect();
+
+        Ok(Self {
+            highlights,
+            bookmarks,
+            notes,
+        })
+    }
+
+    pub fn highlight(&self, book: &Book, chapter: i64, verse: u32) -> Option<HighlightColor> {
+        self.highlights
+            .iter()
+            .find(|h| h.book == *book && h.chapter == chapter && h.verse == verse)
+            .map(|h| h.color)
+    }
+
+    /// Bookmark starting at the verse.
+    pub fn bookmark(&self, book: &Book, chapter: i64, verse: u32) -> Option<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .find(|b| b.book == *book && b.chapter == chapter && b.verse == verse)
+    }
+
+    /// Notes shown after the verse, i.e. those on passages ending there.
+    pub fn notes_after(&self, book: &Book, chapter: i64, verse: u32) -> Vec<&Note> {
+        self.notes
+            .iter()
+            .filter(|n| n.book == *book && n.chapter == chapter && n.end_verse == verse)
+            .collect()
+    }
+
+    /// Highlights merged into runs of consecutive verses with the same colour.
+    pub fn highlight_runs(&self) -> Vec<(Passage, HighlightColor)> {
+        let mut runs: Vec<(Passage, HighlightColor)> = Vec::new();
+        for h in &self.highlights {
+            if let Some((passage, color)) = runs.last_mut() {
+                let end = passage.end_verse.unwrap_or(0);
+                if passage.book == h.book
+                    && passage.chapter == h.chapter
+                    && *color == h.color
+                    && end + 1 == h.verse
+                {
+                    passage.end_verse = Some(h.verse);
+                    continue;
+                }
+            }
+            runs.push((
+                Passage::verses(h.book.clone(), h.chapter, h.verse, h.verse),
+                h.color,
+            ));
+        }
+        runs
+    }
+
+    /// Highlight the verses, or clear them with `None`.
+    pub async fn set_highlight(
+        pool: &SqlitePool,
+        user_id: i64,
+        selection: &Selection,
+        color: Option<HighlightColor>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let book = selection.book as i64;
+        for verse in selection.verse..=selection.end_verse {
+            match color {
+                Some(color) => {
+                    let color = color.slug();
+                    sqlx::query!(
+                        "INSERT INTO highlights (user_id, book, chapter, verse, color)
+                        VALUES (?1, ?2, ?3, ?4, ?5)
+                        ON CONFLICT (user_id, book, chapter, verse) DO UPDATE SET color = excluded.color",
+                        user_id,
+                        book,
+                        selection.chapter,
+                        verse,
+                        color
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query!(
+                        "DELETE FROM highlights WHERE user_id = ? AND book = ? AND chapter = ? AND verse = ?",
+                        user_id,
+                        book,
+                        selection.chapter,
+                        verse
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        tx.commit().await
+    }
+
+    /// Bookmark the verses, or remove the bookmark starting at the same verse.
+    pub async fn toggle_bookmark(
+        pool: &SqlitePool,
+        user_id: i64,
+        selection: &Selection,
+    ) -> Result<(), sqlx::Error> {
+        let book = selection.book as i64;
+        let removed = sqlx::query!(
+            "DELETE FROM bookmarks WHERE user_id = ? AND book = ? AND chapter = ? AND verse = ?",
+            user_id,
+            book,
+            selection.chapter,
+            selection.verse
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        if removed == 0 {
+            sqlx::query!(
+                "INSERT INTO bookmarks (user_id, book, chapter, verse, end_verse) VALUES (?, ?, ?, ?, ?)",
+                user_id,
+                book,
+                selection.chapter,
+                selection.verse,
+                selection.end_verse
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Add a note, or replace the one on exactly the same verses. An empty
+    /// body deletes it.
+    pub async fn save_note(
+        pool: &SqlitePool,
+        user_id: i64,
+        selection: &Selection,
+        body: &str,
+    ) -> Result<(), sqlx::Error> {
+        let book = selection.book as i64;
+        let existing = sqlx::query_scalar!(
+            r#"SELECT id AS "id!" FROM notes
+            WHERE user_id = ? AND book = ? AND chapter = ? AND verse = ? AND end_verse = ?"#,
+            user_id,
+            book,
+            selection.chapter,
+            selection.verse,
+            selection.end_verse
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match (existing, body.trim().is_empty()) {
+            (Some(id), true) => Self::delete_note(pool, user_id, id).await?,
+            (Some(id), false) => {
+                sqlx::query!(
+                    "UPDATE notes SET body = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    body,
+                    id
+                )
+                .execute(pool)
+                .await?;
+            }
+            (None, true) => {}
+            (None, false) => {
+                sqlx::query!(
+                    "INSERT INTO notes (user_id, book, chapter, verse, end_verse, body)
+                    VALUES (?, ?, ?, ?, ?, ?)",
+                    user_id,
+                    book,
+                    selection.chapter,
+                    selection.verse,
+                    selection.end_verse,
+                    body
+                )
+                .execute(pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn delete_note(pool: &SqlitePool, user_id: i64, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM notes WHERE id = ? AND user_id = ?",
+            id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// The chapter block a request came from, to render it again afterwards.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChapterContext {
+    /// `Book::index`
+    pub book: usize,
+    pub chapter: i64,
+    /// Verses of the chapter on screen.
+    pub first: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<u32>,
+}
+
+/// Verses selected in a chapter block.
+#[derive(Debug, Deserialize)]
+pub struct Selection {
+    book: usize,
+    chapter: i64,
+    verse: u32,
+    end_verse: u32,
+}
+
+/// A change to the verses selected in a chapter block. `color` is only used
+/// for highlights, where empty clears them, and `note` for notes.
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    book: usize,
+    chapter: i64,
+    first: u32,
+    last: Option<u32>,
+    verse: u32,
+    end_verse: u32,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    note: String,
+}
+
+impl AnnotationRequest {
+    fn context(&self) -> ChapterContext {
+        ChapterContext {
+            book: self.book,
+            chapter: self.chapter,
+            first: self.first,
+            last: self.last,
+        }
+    }
+}
+
+impl ChapterContext {
+    /// Selected verses in this chapter, if they make sense.
+    fn selection(&self, verse: u32, end_verse: u32) -> Option<Selection> {
+        let book = Book::from_index(self.book)?;
+        let valid = (1..=book.total_chapters() as i64).contains(&self.chapter)
+            && verse >= 1
+            && verse <= end_verse
+            && end_verse <= MAX_VERSES;
+        valid.then_some(Selection {
+            book: self.book,
+            chapter: self.chapter,
+            verse,
+            end_verse,
+        })
+    }
+
+    /// The chapter block with fresh annotations.
+    async fn render(
+        &self,
+        state: &AppState,
+        user_id: i64,
+        settings: &UserSettings,
+    ) -> Result<Markup, ApiError> {
+        let Some(book) = Book::from_index(self.book) else {
+            return Ok(html! {});
+        };
+        let (translation, parallel) = translations(&state.bibles, settings);
+        let view = chapter_view(
+            translation,
+            parallel,
+            &book,
+            self.chapter,
+            (self.first, self.last),
+            settings.locale,
+        )
+        .await?;
+        let annotations = Annotations::for_chapter(&state.db, user_id, &book, self.chapter).await?;
+        Ok(fragment_chapter(&view, &annotations, None, settings.locale))
+    }
+}
+
+pub async fn post_highlight(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<AnnotationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let context = form.context();
+            if let Some(selection) = context.selection(form.verse, form.end_verse) {
+                let color = form.color.parse::<HighlightColor>().ok();
+                Annotations::set_highlight(&state.db, user.id, &selection, color).await?;
+            }
+            Ok(context.render(&state, user.id, &settings).await?)
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+pub async fn post_bookmark(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<AnnotationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let context = form.context();
+            if let Some(selection) = context.selection(form.verse, form.end_verse) {
+                Annotations::toggle_bookmark(&state.db, user.id, &selection).await?;
+            }
+            Ok(context.render(&state, user.id, &settings).await?)
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+pub async fn post_note(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<AnnotationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let context = form.context();
+            if let Some(selection) = context.selection(form.verse, form.end_verse) {
+                Annotations::save_note(&state.db, user.id, &selection, &form.note).await?;
+            }
+            Ok(context.render(&state, user.id, &settings).await?)
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+pub async fn post_delete_note(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Path(id): Path<i64>,
+    Form(context): Form<ChapterContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            Annotations::delete_note(&state.db, user.id, id).await?;
+            Ok(context.render(&state, user.id, &settings).await?)
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// "My notes": everything the user highlighted, bookmarked or wrote.
+pub async fn page_notes(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let annotations = Annotations::from_user(&state.db, user.id).await?;
+            Ok(view::pages::page(
+                locale.t("My notes"),
+                page(&annotations, locale),
+            ))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+fn page(annotations: &Annotations, locale: Locale) -> Markup {
+    let t = |text| locale.t(text);
+    let highlights = annotations.highlight_runs();
+    let is_empty =
+        annotations.notes.is_empty() && annotations.bookmarks.is_empty() && highlights.is_empty();
+
+    html! {
+        div class="flex justify-center" {
+            div class="flex flex-col gap-8 w-[900px] py-8 px-4" {
+                div class="flex justify-between items-center" {
+                    h1 class="text-xl font-bold" { (t("My notes")) }
+                    (ui_button(html! { (t("Back to readings")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).as_link("/"),
+                        &HxCfg::new()
+                    ))
+                }
+
+                @if is_empty {
+                    p class="text-sm text-foreground/60" {
+                        (t("Nothing here yet. Click a verse number while reading to highlight, bookmark or write a note."))
+                    }
+                }
+
+                @if !annotations.notes.is_empty() {
+                    section {
+                        h2 class="font-bold text-md mb-3" { (t("Notes")) }
+                        div class="flex flex-col gap-4" {
+                            @for note in &annotations.notes {
+                                div class="border border-border rounded-sm p-3 bg-background-100" {
+                                    (passage_link(&note.passage(), locale))
+                                    div class="prose prose-sm mt-1 text-sm" { (markdown(&note.body)) }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                @if !annotations.bookmarks.is_empty() {
+                    section {
+                        h2 class="font-bold text-md mb-3" { (t("Bookmarks")) }
+                        div class="flex flex-col gap-1" {
+                            @for bookmark in &annotations.bookmarks {
+                                div class="border-b border-border py-1" {
+                                    (passage_link(&bookmark.passage(), locale))
+                                }
+                            }
+                        }
+                    }
+                }
+
+                @if !highlights.is_empty() {
+                    section {
+                        h2 class="font-bold text-md mb-3" { (t("Highlights")) }
+                        div class="flex flex-col gap-1" {
+                            @for (passage, color) in &highlights {
+                                div class="flex gap-2 items-center border-b border-border py-1" {
+                                    span class=(format!("w-3 h-3 rounded-full {}", color.swatch())) title=(t(color.name())) {}
+                                    (passage_link(passage, locale))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Link to the readings page showing the passage.
+fn passage_link(passage: &Passage, locale: Locale) -> Markup {
+    let href = format!(
+        "/?book={}&chapter={}&verse={}",
+        passage.book.index(),
+        passage.chapter,
+        passage.verse.unwrap_or(1)
+    );
+    html! {
+        a href=(href) class="text-sm font-semibold hover:underline" { (passage.name(locale)) }
+    }
+}
@@ -1,11 +1,16 @@
+use crate::i18n::Locale;
 use lazy_static::lazy_static;
 use std::{
     collections::HashMap,
     fmt::{self, Display},
+    ops::RangeInclusive,
     str::FromStr,
     u16,
 };
 
+/// Verses in the longest chapter, Psalm 119. No verse number is above it.
+pub const MAX_VERSES: u32 = 176;
+
 #[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone)]
 pub enum Book {
     Genesis,
@@ -79,23 +84,27 @@ pub enum Book {
 pub struct BookInfo {
     pub display_to_book: HashMap<&'static str, Book>,
     pub book_to_display: HashMap<Book, &'static str>,
+    pub book_to_indonesian: HashMap<Book, &'static str>,
     pub chapter_map: HashMap<Book, u16>,
     pub index_map: HashMap<Book, usize>,
 }
 
 macro_rules! book_info {
-    ($($book:expr => $name:expr, $chapters:expr, $index:expr),*) => {{
+    ($($book:expr => $name:expr, $indonesian:expr, $chapters:expr, $index:expr),*) => {{
         let mut display_to_book = HashMap::new();
         let mut book_to_display = HashMap::new();
+        let mut book_to_indonesian = HashMap::new();
         let mut chapter_map = HashMap::new();
         let mut index_map = HashMap::new();
         $(display_to_book.insert($name, $book);)*
         $(book_to_display.insert($book, $name);)*
+        $(book_to_indonesian.insert($book, $indonesian);)*
         $(chapter_map.insert($book, $chapters);)*
         $(index_map.insert($book, $index);)*
         BookInfo {
             display_to_book,
             book_to_display,
+            book_to_indonesian,
             chapter_map,
             index_map
         }
@@ -104,72 +113,72 @@ macro_rules! book_info {
 
 lazy_static! {
     static ref BOOK_INFO: BookInfo = book_info![
-        Book::Genesis => "Genesis", 50, 1,
-        Book::Exodus => "Exodus", 40, 2,
-        Book::Leviticus => "Leviticus", 27, 3,
-        Book::Numbers => "Numbers", 36, 4,
-        Book::Deuteronomy => "Deuteronomy", 34, 5,
-        Book::Joshua => "Joshua", 24, 6,
-        Book::Judges => "Judges", 21, 7,
-        Book::Ruth => "Ruth", 4, 8,
-        Book::FirstSamuel => "1 Samuel", 31, 9,
-        Book::SecondSamuel => "2 Samuel", 24, 10,
-        Book::FirstKings => "1 Kings", 22, 11,
-        Book::SecondKings => "2 Kings", 25, 12,
-        Book::FirstChronicles => "1 Chronicles", 29, 13,
-        Book::SecondChronicles => "2 Chronicles", 36, 14,
-        Book::Ezra => "Ezra", 10, 15,
-        Book::Nehemiah => "Nehemiah", 13, 16,
-        Book::Esther => "Esther", 10, 17,
-        Book::Job => "Job", 42, 18,
-        Book::Psalms => "Psalms", 150, 19,
-        Book::Proverbs => "Proverbs", 31, 20,
-        Book::Ecclesiastes => "Ecclesiastes", 12, 21,
-        Book::SongOfSolomon => "Song of Solomon", 8, 22,
-        Book::Isaiah => "Isaiah", 66, 23,
-        Book::Jeremiah => "Jeremiah", 52, 24,
-        Book::Lamentations => "Lamentations", 5, 25,
-        Book::Ezekiel => "Ezekiel", 48, 26,
-        Book::Daniel => "Daniel", 12, 27,
-        Book::Hosea => "Hosea", 14, 28,
-        Book::Joel => "Joel", 3, 29,
-        Book::Amos => "Amos", 9, 30,
-        Book::Obadiah => "Obadiah", 1, 31,
-        Book::Jonah => "Jonah", 4, 32,
-        Book::Micah => "Micah", 7, 33,
-        Book::Nahum => "Nahum", 3, 34,
-        Book::Habakkuk => "Habakkuk", 3, 35,
-        Book::Zephaniah => "Zephaniah", 3, 36,
-        Book::Haggai => "Haggai", 2, 37,
-        Book::Zechariah => "Zechariah", 14, 38,
-        Book::Malachi => "Malachi", 4, 39,
-        Book::Matthew => "Matthew", 28, 40,
-        Book::Mark => "Mark", 16, 41,
-        Book::Luke => "Luke", 24, 42,
-        Book::John => "John", 21, 43,
-        Book::Acts => "Acts", 28, 44,
-        Book::Romans => "Romans", 16, 45,
-        Book::FirstCorinthians => "1 Corinthians", 16, 46,
-        Book::SecondCorinthians => "2 Corinthians", 13, 47,
-        Book::Galatians => "Galatians", 6, 48,
-        Book::Ephesians => "Ephesians", 6, 49,
-        Book::Philippians => "Philippians", 4, 50,
-        Book::Colossians => "Colossians", 4, 51,
-        Book::FirstThessalonians => "1 Thessalonians", 5, 52,
-        Book::SecondThessalonians => "2 Thessalonians", 3, 53,
-        Book::FirstTimothy => "1 Timothy", 6, 54,
-        Book::SecondTimothy => "2 Timothy", 4, 55,
-        Book::Titus => "Titus", 3, 56,
-        Book::Philemon => "Philemon", 1, 57,
-        Book::Hebrews => "Hebrews", 13, 58,
-        Book::James => "James", 5, 59,
-        Book::FirstPeter => "1 Peter", 5, 60,
-        Book::SecondPeter => "2 Peter", 3, 61,
-        Book::FirstJohn => "1 John", 5, 62,
-        Book::SecondJohn => "2 John", 1, 63,
-        Book::ThirdJohn => "3 John", 1, 64,
-        Book::Jude => "Jude", 1, 65,
-        Book::Revelation => "Revelation", 22, 66
+        Book::Genesis => "Genesis", "Kejadian", 50, 1,
+        Book::Exodus => "Exodus", "Keluaran", 40, 2,
+        Book::Leviticus => "Leviticus", "Imamat", 27, 3,
+        Book::Numbers => "Numbers", "Bilangan", 36, 4,
+        Book::Deuteronomy => "Deuteronomy", "Ulangan", 34, 5,
+        Book::Joshua => "Joshua", "Yosua", 24, 6,
+        Book::Judges => "Judges", "Hakim-hakim", 21, 7,
+        Book::Ruth => "Ruth", "Rut", 4, 8,
+        Book::FirstSamuel => "1 Samuel", "1 Samuel", 31, 9,
+        Book::SecondSamuel => "2 Samuel", "2 Samuel", 24, 10,
+        Book::FirstKings => "1 Kings", "1 Raja-raja", 22, 11,
+        Book::SecondKings => "2 Kings", "2 Raja-raja", 25, 12,
+        Book::FirstChronicles => "1 Chronicles", "1 Tawarikh", 29, 13,
+        Book::SecondChronicles => "2 Chronicles", "2 Tawarikh", 36, 14,
+        Book::Ezra => "Ezra", "Ezra", 10, 15,
+        Book::Nehemiah => "Nehemiah", "Nehemia", 13, 16,
+        Book::Esther => "Esther", "Ester", 10, 17,
+        Book::Job => "Job", "Ayub", 42, 18,
+        Book::Psalms => "Psalms", "Mazmur", 150, 19,
+        Book::Proverbs => "Proverbs", "Amsal", 31, 20,
+        Book::Ecclesiastes => "Ecclesiastes", "Pengkhotbah", 12, 21,
+        Book::SongOfSolomon => "Song of Solomon", "Kidung Agung", 8, 22,
+        Book::Isaiah => "Isaiah", "Yesaya", 66, 23,
+        Book::Jeremiah => "Jeremiah", "Yeremia", 52, 24,
+        Book::Lamentations => "Lamentations", "Ratapan", 5, 25,
+        Book::Ezekiel => "Ezekiel", "Yehezkiel", 48, 26,
+        Book::Daniel => "Daniel", "Daniel", 12, 27,
+        Book::Hosea => "Hosea", "Hosea", 14, 28,
+        Book::Joel => "Joel", "Yoel", 3, 29,
+        Book::Amos => "Amos", "Amos", 9, 30,
+        Book::Obadiah => "Obadiah", "Obaja", 1, 31,
+        Book::Jonah => "Jonah", "Yunus", 4, 32,
+        Book::Micah => "Micah", "Mikha", 7, 33,
+        Book::Nahum => "Nahum", "Nahum", 3, 34,
+        Book::Habakkuk => "Habakkuk", "Habakuk", 3, 35,
+        Book::Zephaniah => "Zephaniah", "Zefanya", 3, 36,
+        Book::Haggai => "Haggai", "Hagai", 2, 37,
+        Book::Zechariah => "Zechariah", "Zakharia", 14, 38,
+        Book::Malachi => "Malachi", "Maleakhi", 4, 39,
+        Book::Matthew => "Matthew", "Matius", 28, 40,
+        Book::Mark => "Mark", "Markus", 16, 41,
+        Book::Luke => "Luke", "Lukas", 24, 42,
+        Book::John => "John", "Yohanes", 21, 43,
+        Book::Acts => "Acts", "Kisah Para Rasul", 28, 44,
+        Book::Romans => "Romans", "Roma", 16, 45,
+        Book::FirstCorinthians => "1 Corinthians", "1 Korintus", 16, 46,
+        Book::SecondCorinthians => "2 Corinthians", "2 Korintus", 13, 47,
+        Book::Galatians => "Galatians", "Galatia", 6, 48,
+        Book::Ephesians => "Ephesians", "Efesus", 6, 49,
+        Book::Philippians => "Philippians", "Filipi", 4, 50,
+        Book::Colossians => "Colossians", "Kolose", 4, 51,
+        Book::FirstThessalonians => "1 Thessalonians", "1 Tesalonika", 5, 52,
+        Book::SecondThessalonians => "2 Thessalonians", "2 Tesalonika", 3, 53,
+        Book::FirstTimothy => "1 Timothy", "1 Timotius", 6, 54,
+        Book::SecondTimothy => "2 Timothy", "2 Timotius", 4, 55,
+        Book::Titus => "Titus", "Titus", 3, 56,
+        Book::Philemon => "Philemon", "Filemon", 1, 57,
+        Book::Hebrews => "Hebrews", "Ibrani", 13, 58,
+        Book::James => "James", "Yakobus", 5, 59,
+        Book::FirstPeter => "1 Peter", "1 Petrus", 5, 60,
+        Book::SecondPeter => "2 Peter", "2 Petrus", 3, 61,
+        Book::FirstJohn => "1 John", "1 Yohanes", 5, 62,
+        Book::SecondJohn => "2 John", "2 Yohanes", 1, 63,
+        Book::ThirdJohn => "3 John", "3 Yohanes", 1, 64,
+        Book::Jude => "Jude", "Yudas", 1, 65,
+        Book::Revelation => "Revelation", "Wahyu", 22, 66
     ];
 }
 
@@ -192,6 +201,13 @@ impl FromStr for Book {
 }
 
 impl Book {
+    /// All books in canonical order, Genesis to Revelation.
+    pub fn all() -> Vec<Book> {
+        let mut books: Vec<_> = BOOK_INFO.index_map.keys().cloned().collect();
+        books.sort();
+        books
+    }
+
     pub fn total_chapters(&self) -> u16 {
         BOOK_INFO.chapter_map[self]
     }
@@ -199,15 +215,157 @@ impl Book {
     pub fn index(&self) -> usize {
         BOOK_INFO.index_map[self]
     }
+
+    /// Name shown to users. `Display` stays English as it's also the stored form.
+    pub fn name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => BOOK_INFO.book_to_display[self],
+            Locale::Id => BOOK_INFO.book_to_indonesian[self],
+        }
+    }
+
+    /// Inverse of `index`, 1 for Genesis to 66 for Revelation.
+    pub fn from_index(index: usize) -> Option<Book> {
+        index
+            .checked_sub(1)
+            .and_then(|i| Book::all().into_iter().nth(i))
+    }
 }
 
-pub struct ChapterInfo {
+/// A contiguous run of verses in one book, e.g. "Psalms 119:1-32", "Genesis 1-3"
+/// or "John 3:16-4:2". `None` verses stand for the whole first or last chapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passage {
     pub book: Book,
     pub chapter: i64,
+    pub verse: Option<u32>,
+    pub end_chapter: i64,
+    pub end_verse: Option<u32>,
+}
+
+impl Passage {
+    pub fn chapter(book: Book, chapter: i64) -> Self {
+        Self {
+            book,
+            chapter,
+            verse: None,
+            end_chapter: chapter,
+            end_verse: None,
+        }
+    }
+
+    pub fn verses(book: Book, chapter: i64, from: u32, to: u32) -> Self {
+        Self {
+            book,
+            chapter,
+            verse: Some(from),
+            end_chapter: chapter,
+            end_verse: Some(to),
+        }
+    }
+
+    /// Chapters the passage touches, including partially read ones.
+    pub fn chapters(&self) -> RangeInclusive<i64> {
+        self.chapter..=self.end_chapter
+    }
+
+    /// (first, last) verse of `chapter` inside the passage, `None` meaning up to the end.
+    pub fn verse_range(&self, chapter: i64) -> (u32, Option<u32>) {
+        let first = match self.verse {
+            Some(verse) if chapter == self.chapter => verse,
+            _ => 1,
+        };
+        let last = if chapter == self.end_chapter {
+            self.end_verse
+        } else {
+            None
+        };
+        (first, last)
+    }
+
+    pub fn is_whole_chapters(&self) -> bool {
+        self.verse.is_none() && self.end_verse.is_none()
+    }
+
+    /// Extend the passage with `next` if it carries on where this one ends.
+    pub fn join(&mut self, next: &Passage) -> bool {
+        let continues = self.book == next.book
+            && match (self.end_verse, next.verse) {
+                (None, None) => next.chapter == self.end_chapter + 1,
+                (Some(end), Some(start)) => next.chapter == self.end_chapter && start == end + 1,
+                _ => false,
+            };
+        if continues {
+            self.end_chapter = next.end_chapter;
+            self.end_verse = next.end_verse;
+        }
+        continues
+    }
+
+    /// The reference without the book name, e.g. "119:1-32".
+    pub fn reference(&self) -> String {
+        let start = match self.verse {
+            Some(verse) => format!("{}:{}", self.chapter, verse),
+            None => self.chapter.to_string(),
+        };
+        match (self.end_chapter == self.chapter, self.end_verse) {
+            (true, None) if self.verse.is_none() => start,
+            (true, None) => format!("{start}ff"),
+            (true, Some(end)) if Some(end) == self.verse => start,
+            (true, Some(end)) => format!("{start}-{end}"),
+            (false, None) => format!("{start}-{}", self.end_chapter),
+            (false, Some(end)) => format!("{start}-{}:{}", self.end_chapter, end),
+        }
+    }
+
+    /// Like `Display`, with the book name in `locale`.
+    pub fn name(&self, locale: Locale) -> String {
+        format!("{} {}", self.book.name(locale), self.reference())
+    }
+}
+
+impl Display for Passage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.book, self.reference())
+    }
 }
 
-/// (ChapterInfo, Total Chapters)
-pub fn get_day_plan(books: &[Book], day: i64) -> (ChapterInfo, i64) {
+/// Verse ranges long chapters are split into when read in sections.
+fn chapter_sections(book: &Book, chapter: i64) -> Option<&'static [(u32, u32)]> {
+    match (book, chapter) {
+        (Book::Psalms, 119) => Some(&[
+            (1, 32),
+            (33, 64),
+            (65, 96),
+            (97, 128),
+            (129, 152),
+            (153, 176),
+        ]),
+        _ => None,
+    }
+}
+
+/// The books as a sequence of daily units: whole chapters, with long chapters
+/// broken up into sections when `sections` is set.
+pub fn reading_units(books: &[Book], sections: bool) -> Vec<Passage> {
+    let mut units = Vec::new();
+    for book in books {
+        for chapter in 1..=book.total_chapters() as i64 {
+            match chapter_sections(book, chapter).filter(|_| sections) {
+                Some(ranges) => units.extend(
+                    ranges
+                        .iter()
+                        .map(|(from, to)| Passage::verses(book.clone(), chapter, *from, *to)),
+                ),
+                None => units.push(Passage::chapter(book.clone(), chapter)),
+            }
+        }
+    }
+    units
+}
+
+/// (Passage, Total Chapters)
+pub fn get_day_plan(books: &[Book], day: i64) -> (Passage, i64) {
     let total_chapters = books.iter().fold(0, |sum, val| sum + val.total_chapters()) as i64;
 
     let remainder = day % total_chapters;
@@ -216,21 +374,12 @@ pub fn get_day_plan(books: &[Book], day: i64) -> (ChapterInfo, i64) {
         if r == 0 {
             let last = books.last().expect("no last element");
             return (
-                ChapterInfo {
-                    book: last.clone(),
-                    chapter: last.total_chapters() as i64,
-                },
+                Passage::chapter(last.clone(), last.total_chapters() as i64),
                 total_chapters,
             );
         }
         if r <= book.total_chapters() as i64 {
-            return (
-                ChapterInfo {
-                    book: book.clone(),
-                    chapter: r as i64,
-                },
-                total_chapters,
-            );
+            return (Passage::chapter(book.clone(), r), total_chapters);
         }
         r -= book.total_chapters() as i64;
     }
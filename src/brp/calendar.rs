@@ -0,0 +1,289 @@
+use super::{
+    model::{UserDates, UserReadings, UserSettings},
+    portion_label,
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    utils::today_naive_date,
+    view::{
+        hx::HxCfg,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use maud::{html, Markup};
+use rand::RngCore;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+/// Days covered by the feed unless asked otherwise.
+const DEFAULT_DAYS: i64 = 30;
+const MAX_DAYS: i64 = 366;
+
+fn hash_token(token: &str) -> String {
+    sha256::digest(token)
+}
+
+/// Whether the user has a feed URL. Only its hash is stored, so the URL
+/// itself is shown once on creation.
+pub async fn has_calendar_token(pool: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    Ok(sqlx::query!(
+        "SELECT user_id FROM calendar_tokens WHERE user_id = ?",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .is_some())
+}
+
+/// Create a new feed secret for the user, replacing (and so revoking) the old one.
+pub async fn create_calendar_token(pool: &SqlitePool, user_id: i64) -> Result<String, sqlx::Error> {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    let hash = hash_token(&token);
+    sqlx::query!(
+        "INSERT INTO calendar_tokens (user_id, token_hash) VALUES (?1, ?2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET token_hash = excluded.token_hash, created_at = CURRENT_TIMESTAMP",
+        user_id,
+        hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(token)
+}
+
+pub async fn revoke_calendar_token(pool: &SqlitePool, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM calendar_tokens WHERE user_id = ?", user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// iCalendar document with one all-day event per day, listing the portion of
+/// every reading list due that day.
+pub fn ics_feed(
+    user_id: i64,
+    readings: &UserReadings,
+    dates: &UserDates,
+    days: i64,
+    locale: Locale,
+) -> String {
+    let today = today_naive_date();
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    // UIDs must be unique across feeds, as calendar apps may merge events of
+    // several subscriptions. The id itself shouldn't show up in the feed.
+    let user = &sha256::digest(user_id.to_string())[..16];
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//brp-web//Bible reading plan//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(locale.t("Bible reading"))),
+    ];
+    for day in 0..days {
+        let date = today + Duration::days(day);
+        let portions: Vec<_> = readings
+            .readings
+            .iter()
+            .map(|reading| (reading, reading.upcoming_portion(dates, day)))
+            .filter(|(_, portion)| !portion.is_empty())
+            .collect();
+        if portions.is_empty() {
+            continue;
+        }
+
+        let summary = portions
+            .iter()
+            .map(|(_, portion)| portion_label(portion, locale))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let description = portions
+            .iter()
+            .map(|(reading, portion)| {
+                format!(
+                    "{}: {}",
+                    reading.label(locale),
+                    portion_label(portion, locale)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{user}-reading@brp-web", date.format("%Y%m%d")),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", ics_date(date)),
+            format!("DTEND;VALUE=DATE:{}", ics_date(date + Duration::days(1))),
+            format!("SUMMARY:{}", escape_text(&summary)),
+            format!("DESCRIPTION:{}", escape_text(&description)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn ics_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Escape a TEXT value (RFC 5545 3.3.11).
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Terminate a content line with CRLF, folding it into lines of at most 75
+/// octets without splitting characters (RFC 5545 3.1).
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    days: Option<i64>,
+}
+
+/// The feed at `/calendar/{token}.ics`. Unknown or revoked tokens get a 404,
+/// as calendar apps don't log in.
+pub async fn get_calendar(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let token = file.strip_suffix(".ics").unwrap_or(&file);
+    let hash = hash_token(token);
+    let user_id = sqlx::query!(
+        "SELECT user_id FROM calendar_tokens WHERE token_hash = ?",
+        hash
+    )
+    .fetch_optional(&state.db)
+    .await?
+    .map(|rec| rec.user_id);
+    let Some(user_id) = user_id else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let locale = UserSettings::from_user(&state.db, user_id).await?.locale;
+    let dates = UserDates::from_user_or_set_default(&state.db, user_id).await;
+    let readings = UserReadings::from_user(&state.db, user_id).await;
+    let days = query.days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics_feed(user_id, &readings, &dates, days, locale),
+    )
+        .into_response())
+}
+
+/// The calendar section of the settings page. `created` is the secret of a
+/// feed URL just created, shown this one time only.
+pub fn fragment_calendar(has_token: bool, created: Option<&str>, locale: Locale) -> Markup {
+    let post = |url| {
+        HxCfg::new()
+            .with_post(url)
+            .with_target("#calendar-feed")
+            .with_swap("outerHTML")
+    };
+    html! {
+        section id="calendar-feed" {
+            h2 class="font-bold text-md mb-1" { (locale.t("Calendar feed")) }
+            p class="text-sm text-foreground/60 mb-4" {
+                (locale.t("Subscribe to this address in a calendar app to see the readings of the coming days. Anyone who has the address can see them, so revoke it if it leaks."))
+            }
+            @if let Some(token) = created {
+                div class="flex flex-col gap-1 mb-4 p-3 border border-green-600 rounded-sm" {
+                    p class="text-sm text-green-600" {
+                        (locale.t("Copy the address now, it won't be shown again."))
+                    }
+                    // Absolute URL, completed in the browser.
+                    input type="text" readonly value=(format!("/calendar/{token}.ics"))
+                        x-data x-init="$el.value = location.origin + $el.value"
+                        "@click"="$el.select()"
+                        class="w-full text-sm font-mono rounded-xs border-border bg-background py-1.5";
+                }
+            }
+            @if has_token {
+                div class="flex gap-2 items-center" {
+                    (ui_button(html! { (locale.t("New address")) },
+                        &ButtonCfg::new().with_color(Color::Alternative),
+                        &post("/settings/calendar")
+                    ))
+                    (ui_button(html! { (locale.t("Revoke")) },
+                        &ButtonCfg::new().with_color(Color::Default),
+                        &post("/settings/calendar/revoke")
+                    ))
+                }
+            } @else {
+                (ui_button(html! { (locale.t("Create address")) },
+                    &ButtonCfg::new().with_color(Color::Alternative),
+                    &post("/settings/calendar")
+                ))
+            }
+        }
+    }
+}
+
+pub async fn post_calendar_token(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let token = create_calendar_token(&state.db, user.id).await?;
+            Ok(fragment_calendar(true, Some(&token), locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+pub async fn post_calendar_revoke(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            revoke_calendar_token(&state.db, user.id).await?;
+            Ok(fragment_calendar(false, None, locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
@@ -1,7 +1,8 @@
 use super::books::Book;
 use reqwest::Client as ReqwestClient;
 use serde::Deserialize;
-use std::{future::Future, io, path::PathBuf};
+use sqlx::SqlitePool;
+use std::{collections::BTreeMap, future::Future, io, path::PathBuf, sync::Arc};
 use thiserror::Error;
 
 pub trait ChapterDispatcher {
@@ -12,8 +13,41 @@ pub trait ChapterDispatcher {
     ) -> impl Future<Output = Result<Bible, ChapterError>> + Send;
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct IndonesianBible;
 
+/// A translation imported into the `verses` table with the `import-bible` command.
+#[derive(Debug, Clone)]
+pub struct StoredBible {
+    pool: SqlitePool,
+    translation: String,
+}
+
+/// Where the chapters of a translation come from.
+#[derive(Debug, Clone)]
+pub enum BibleSource {
+    Sabda(IndonesianBible),
+    Stored(StoredBible),
+}
+
+/// Code of the translation served by alkitab.sabda.org.
+pub const SABDA_TRANSLATION: &str = "id-tb";
+
+#[derive(Debug, Clone)]
+pub struct Translation {
+    /// e.g. `id-tb`, `en-kjv`, `en-web`.
+    pub code: String,
+    pub name: String,
+    pub source: BibleSource,
+}
+
+/// The translations a user can choose from, keyed by code.
+#[derive(Debug, Clone)]
+pub struct BibleRegistry {
+    translations: Arc<Vec<Translation>>,
+    default: String,
+}
+
 #[derive(Debug, Error)]
 pub enum ChapterError {
     #[error("Error fetching chapter content: {0}")]
@@ -24,6 +58,12 @@ pub enum ChapterError {
 
     #[error("Content deserialization error: {0}")]
     DeserializationError(#[from] serde_xml_rs::Error),
+
+    #[error("Error reading stored chapter: {0}")]
+    StoreError(#[from] sqlx::Error),
+
+    #[error("Chapter {0} {1} is not in the store")]
+    NotFound(Book, usize),
 }
 
 impl ChapterDispatcher for IndonesianBible {
@@ -61,6 +101,107 @@ impl ChapterDispatcher for IndonesianBible {
     }
 }
 
+impl StoredBible {
+    pub fn new(pool: SqlitePool, translation: impl Into<String>) -> Self {
+        Self {
+            pool,
+            translation: translation.into(),
+        }
+    }
+}
+
+impl ChapterDispatcher for StoredBible {
+    async fn get_chapter(&self, book: &Book, chapter_num: usize) -> Result<Bible, ChapterError> {
+        let book_idx = book.index() as i64;
+        let chapter = chapter_num as i64;
+        let verses = sqlx::query!(
+            "SELECT verse, title, text FROM verses
+            WHERE translation = ?1 AND book = ?2 AND chapter = ?3
+            ORDER BY verse",
+            self.translation,
+            book_idx,
+            chapter
+        )
+        .fetch_all(&self.pool)
+        .await
+        .inspect_err(|e| tracing::error!("can't query stored chapter: {}", e))?;
+        if verses.is_empty() {
+            return Err(ChapterError::NotFound(book.clone(), chapter_num));
+        }
+
+        Ok(Bible {
+            title: format!("{} {}", book, chapter_num),
+            book: book.index() as u32,
+            bookname: book.to_string(),
+            chapter: chapter_num as u32,
+            chapter_count: book.total_chapters() as u32,
+            verses: Verses {
+                verse: verses
+                    .into_iter()
+                    .map(|v| Verse {
+                        number: v.verse as u32,
+                        title: v.title,
+                        text: v.text,
+                    })
+                    .collect(),
+            },
+        })
+    }
+}
+
+impl BibleRegistry {
+    /// Alkitab Sabda's Terjemahan Baru plus every translation in the verse store.
+    /// An imported `id-tb` takes the place of the remote one.
+    pub async fn load(pool: &SqlitePool, default: Option<String>) -> Result<Self, sqlx::Error> {
+        let stored = sqlx::query!("SELECT code, name FROM translations ORDER BY code")
+            .fetch_all(pool)
+            .await?;
+
+        let mut translations = Vec::with_capacity(stored.len() + 1);
+        if !stored.iter().any(|t| t.code == SABDA_TRANSLATION) {
+            translations.push(Translation {
+                code: SABDA_TRANSLATION.to_string(),
+                name: "Terjemahan Baru (alkitab.sabda.org)".to_string(),
+                source: BibleSource::Sabda(IndonesianBible),
+            });
+        }
+        translations.extend(stored.into_iter().map(|t| Translation {
+            source: BibleSource::Stored(StoredBible::new(pool.clone(), t.code.clone())),
+            code: t.code,
+            name: t.name,
+        }));
+
+        Ok(Self {
+            translations: Arc::new(translations),
+            default: default.unwrap_or_else(|| SABDA_TRANSLATION.to_string()),
+        })
+    }
+
+    pub fn all(&self) -> &[Translation] {
+        &self.translations
+    }
+
+    /// The translation with the given code, or the default one if it's unknown.
+    pub fn get(&self, code: Option<&str>) -> &Translation {
+        code.and_then(|code| self.find(code))
+            .or_else(|| self.find(&self.default))
+            .unwrap_or(&self.translations[0])
+    }
+
+    fn find(&self, code: &str) -> Option<&Translation> {
+        self.translations.iter().find(|t| t.code == code)
+    }
+}
+
+impl ChapterDispatcher for BibleSource {
+    async fn get_chapter(&self, book: &Book, chapter_num: usize) -> Result<Bible, ChapterError> {
+        match self {
+            BibleSource::Sabda(bible) => bible.get_chapter(book, chapter_num).await,
+            BibleSource::Stored(bible) => bible.get_chapter(book, chapter_num).await,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Bible {
     pub title: String,
@@ -82,3 +223,19 @@ pub struct Verse {
     pub title: Option<String>,
     pub text: String,
 }
+
+/// Pair up the verses of two translations of a chapter by `Verse.number`,
+/// leaving a gap where one of them doesn't have the verse.
+pub fn align_verses<'a>(
+    left: &[&'a Verse],
+    right: &[&'a Verse],
+) -> Vec<(Option<&'a Verse>, Option<&'a Verse>)> {
+    let mut rows: BTreeMap<u32, (Option<&Verse>, Option<&Verse>)> = BTreeMap::new();
+    for verse in left {
+        rows.entry(verse.number).or_default().0 = Some(verse);
+    }
+    for verse in right {
+        rows.entry(verse.number).or_default().1 = Some(verse);
+    }
+    rows.into_values().collect()
+}
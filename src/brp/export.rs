@@ -0,0 +1,605 @@
+use super::{
+    annotations::{Annotations, HighlightColor},
+    books::{Book, MAX_VERSES},
+    journal::JournalEntry,
+    model::{UserDates, UserReadings, UserSettings},
+    plan::Rule,
+};
+use crate::{
+    auth::User, errors::ApiError, i18n::Locale, view::pages::login::redirect_login, AppState,
+};
+use axum::{
+    extract::{Multipart, State},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Bumped whenever the archive layout changes in a way older readers can't handle.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Everything a user has stored, as downloaded from `/export`. Books are stored
+/// by their English name, like in the `readings` table.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub settings: ArchiveSettings,
+    pub dates: ArchiveDates,
+    pub readings: Vec<ArchiveReading>,
+    /// The completion log.
+    pub progress: Vec<ArchiveProgress>,
+    pub highlights: Vec<ArchiveHighlight>,
+    pub bookmarks: Vec<ArchiveBookmark>,
+    pub notes: Vec<ArchiveNote>,
+    pub journal: Vec<ArchiveJournalEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveSettings {
+    pub translation: Option<String>,
+    pub parallel: Option<String>,
+    pub locale: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveDates {
+    pub start_date: NaiveDate,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveReading {
+    pub books: Vec<String>,
+    /// See `Rule`'s `Display`, e.g. "cycle" or "spread:365".
+    pub rule: String,
+    pub catch_up: bool,
+    pub position: i64,
+    pub start_date: Option<NaiveDate>,
+    pub paused_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveProgress {
+    /// Index into `Archive::readings`, `None` for lists removed since.
+    pub list: Option<usize>,
+    pub book: String,
+    pub chapter: i64,
+    pub read_on: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveHighlight {
+    pub book: String,
+    pub chapter: i64,
+    pub verse: u32,
+    pub color: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveBookmark {
+    pub book: String,
+    pub chapter: i64,
+    pub verse: u32,
+    pub end_verse: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveNote {
+    pub book: String,
+    pub chapter: i64,
+    pub verse: u32,
+    pub end_verse: u32,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveJournalEntry {
+    pub date: NaiveDate,
+    pub body: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Not an export archive: {0}")]
+    Malformed(#[from] serde_json::Error),
+
+    #[error("Unsupported archive version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("The archive has no reading lists")]
+    NoReadings,
+
+    #[error("Unknown book \"{0}\"")]
+    UnknownBook(String),
+
+    #[error("{0} has no chapter {1}")]
+    NoSuchChapter(Book, i64),
+
+    #[error("Invalid verses {0}-{1}")]
+    InvalidVerses(u32, u32),
+
+    #[error("Invalid rule \"{0}\"")]
+    InvalidRule(String),
+
+    #[error("Unknown highlight color \"{0}\"")]
+    InvalidColor(String),
+
+    #[error("Progress refers to missing list {0}")]
+    UnknownList(usize),
+
+    #[error("More than one journal entry for {0}")]
+    DuplicateJournalEntry(NaiveDate),
+
+    #[error("Error storing the archive: {0}")]
+    StoreError(#[from] sqlx::Error),
+}
+
+impl Archive {
+    pub async fn from_user(pool: &SqlitePool, user_id: i64) -> Result<Self, sqlx::Error> {
+        let settings = UserSettings::from_user(pool, user_id).await?;
+        let dates = UserDates::from_user_or_set_default(pool, user_id).await;
+        let readings = UserReadings::from_user(pool, user_id).await;
+        let annotations = Annotations::from_user(pool, user_id).await?;
+        let journal = JournalEntry::all_from_user(pool, user_id).await?;
+
+        let progress = sqlx::query!(
+            "SELECT reading_idx, book, chapter, read_on FROM progress
+            WHERE user_id = ? ORDER BY read_on, reading_idx, id",
+            user_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|rec| ArchiveProgress {
+            list: usize::try_from(rec.reading_idx).ok(),
+            book: rec.book,
+            chapter: rec.chapter,
+            read_on: rec.read_on,
+        })
+        .collect();
+
+        Ok(Self {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            settings: ArchiveSettings {
+                translation: settings.translation,
+                parallel: settings.parallel,
+                locale: settings.locale.code().to_string(),
+            },
+            dates: ArchiveDates {
+                start_date: dates.start_date,
+                offset: dates.offset,
+            },
+            readings: readings
+                .readings
+                .into_iter()
+                .map(|reading| ArchiveReading {
+                    books: reading.books.iter().map(|b| b.to_string()).collect(),
+                    rule: reading.rule.to_string(),
+                    catch_up: reading.catch_up,
+                    position: reading.position,
+                    start_date: reading.start_date,
+                    paused_on: reading.paused_on,
+                })
+                .collect(),
+            progress,
+            highlights: annotations
+                .highlights
+                .into_iter()
+                .map(|h| ArchiveHighlight {
+                    book: h.book.to_string(),
+                    chapter: h.chapter,
+                    verse: h.verse,
+                    color: h.color.slug().to_string(),
+                })
+                .collect(),
+            bookmarks: annotations
+                .bookmarks
+                .into_iter()
+                .map(|b| ArchiveBookmark {
+                    book: b.book.to_string(),
+                    chapter: b.chapter,
+                    verse: b.verse,
+                    end_verse: b.end_verse,
+                })
+                .collect(),
+            notes: annotations
+                .notes
+                .into_iter()
+                .map(|n| ArchiveNote {
+                    book: n.book.to_string(),
+                    chapter: n.chapter,
+                    verse: n.verse,
+                    end_verse: n.end_verse,
+                    body: n.body,
+                })
+                .collect(),
+            journal: journal
+                .into_iter()
+                .map(|entry| ArchiveJournalEntry {
+                    date: entry.date,
+                    body: entry.body,
+                })
+                .collect(),
+        })
+    }
+
+    /// Parse and validate an uploaded archive.
+    pub fn parse(input: &[u8]) -> Result<Self, ArchiveError> {
+        let archive: Self = serde_json::from_slice(input)?;
+        archive.validate()?;
+        Ok(archive)
+    }
+
+    fn validate(&self) -> Result<(), ArchiveError> {
+        if self.version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(self.version));
+        }
+        if self.readings.is_empty() {
+            return Err(ArchiveError::NoReadings);
+        }
+        for reading in &self.readings {
+            parse_rule(&reading.rule)?;
+            if reading.books.is_empty() {
+                return Err(ArchiveError::NoReadings);
+            }
+            for book in &reading.books {
+                parse_book(book)?;
+            }
+        }
+        for p in &self.progress {
+            if let Some(list) = p.list.filter(|list| *list >= self.readings.len()) {
+                return Err(ArchiveError::UnknownList(list));
+            }
+            parse_chapter(&p.book, p.chapter)?;
+        }
+        for h in &self.highlights {
+            parse_verses(&h.book, h.chapter, h.verse, h.verse)?;
+            parse_color(&h.color)?;
+        }
+        for b in &self.bookmarks {
+            parse_verses(&b.book, b.chapter, b.verse, b.end_verse)?;
+        }
+        for n in &self.notes {
+            parse_verses(&n.book, n.chapter, n.verse, n.end_verse)?;
+        }
+        let mut dates = HashSet::new();
+        for entry in &self.journal {
+            if !dates.insert(entry.date) {
+                return Err(ArchiveError::DuplicateJournalEntry(entry.date));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace all data of the user with the archive's. Only call on a validated archive.
+    pub async fn restore(&self, pool: &SqlitePool, user_id: i64) -> Result<(), ArchiveError> {
+        let mut tx = pool.begin().await?;
+
+        for table in [
+            "readings",
+            "dates",
+            "user_settings",
+            "progress",
+            "highlights",
+            "bookmarks",
+            "notes",
+            "journal",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE user_id = ?"))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let locale = self
+            .settings
+            .locale
+            .parse::<Locale>()
+            .unwrap_or_default()
+            .code();
+        sqlx::query!(
+            "INSERT INTO user_settings (user_id, translation, parallel, locale) VALUES (?1, ?2, ?3, ?4)",
+            user_id,
+            self.settings.translation,
+            self.settings.parallel,
+            locale
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO dates (user_id, start_date, offset) VALUES (?1, ?2, ?3)",
+            user_id,
+            self.dates.start_date,
+            self.dates.offset
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (idx, reading) in self.readings.iter().enumerate() {
+            let idx = idx as i64;
+            let books = reading
+                .books
+                .iter()
+                .map(|b| parse_book(b).map(|b| b.to_string()))
+                .collect::<Result<Vec<_>, _>>()?
+                .join("|");
+            let rule = parse_rule(&reading.rule)?.to_string();
+            sqlx::query!(
+                "INSERT INTO readings (user_id, reading, idx, rule, catch_up, position, start_date, paused_on)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                user_id,
+                books,
+                idx,
+                rule,
+                reading.catch_up,
+                reading.position,
+                reading.start_date,
+                reading.paused_on
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for p in &self.progress {
+            let idx = p.list.map_or(-1, |list| list as i64);
+            let book = parse_book(&p.book)?.to_string();
+            sqlx::query!(
+                "INSERT OR IGNORE INTO progress (user_id, reading_idx, book, chapter, read_on)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                user_id,
+                idx,
+                book,
+                p.chapter,
+                p.read_on
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for h in &self.highlights {
+            let book = parse_book(&h.book)?.index() as i64;
+            let color = parse_color(&h.color)?.slug();
+            sqlx::query!(
+                "INSERT OR REPLACE INTO highlights (user_id, book, chapter, verse, color)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                user_id,
+                book,
+                h.chapter,
+                h.verse,
+                color
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Exported newest first, so insert oldest first.
+        for b in self.bookmarks.iter().rev() {
+            let book = parse_book(&b.book)?.index() as i64;
+            sqlx::query!(
+                "INSERT OR REPLACE INTO bookmarks (user_id, book, chapter, verse, end_verse)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                user_id,
+                book,
+                b.chapter,
+                b.verse,
+                b.end_verse
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for n in self.notes.iter().rev() {
+            let book = parse_book(&n.book)?.index() as i64;
+            sqlx::query!(
+                "INSERT INTO notes (user_id, book, chapter, verse, end_verse, body)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                user_id,
+                book,
+                n.chapter,
+                n.verse,
+                n.end_verse,
+                n.body
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for entry in &self.journal {
+            sqlx::query!(
+                "INSERT INTO journal (user_id, entry_date, body) VALUES (?1, ?2, ?3)",
+                user_id,
+                entry.date,
+                entry.body
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn parse_book(name: &str) -> Result<Book, ArchiveError> {
+    name.parse::<Book>()
+        .map_err(|_| ArchiveError::UnknownBook(name.to_string()))
+}
+
+fn parse_chapter(book: &str, chapter: i64) -> Result<Book, ArchiveError> {
+    let book = parse_book(book)?;
+    if chapter < 1 || chapter > book.total_chapters() as i64 {
+        return Err(ArchiveError::NoSuchChapter(book, chapter));
+    }
+    Ok(book)
+}
+
+fn parse_verses(
+    book: &str,
+    chapter: i64,
+    verse: u32,
+    end_verse: u32,
+) -> Result<Book, ArchiveError> {
+    let book = parse_chapter(book, chapter)?;
+    if verse < 1 || end_verse < verse || end_verse > MAX_VERSES {
+        return Err(ArchiveError::InvalidVerses(verse, end_verse));
+    }
+    Ok(book)
+}
+
+fn parse_rule(rule: &str) -> Result<Rule, ArchiveError> {
+    rule.parse::<Rule>()
+        .map_err(|_| ArchiveError::InvalidRule(rule.to_string()))
+}
+
+fn parse_color(color: &str) -> Result<HighlightColor, ArchiveError> {
+    color
+        .parse::<HighlightColor>()
+        .map_err(|_| ArchiveError::InvalidColor(color.to_string()))
+}
+
+/// The reading log as CSV, one row per chapter marked as read.
+pub async fn reading_log_csv(pool: &SqlitePool, user_id: i64) -> Result<Vec<u8>, ApiError> {
+    let rows = sqlx::query!(
+        "SELECT read_on, reading_idx, book, chapter FROM progress
+        WHERE user_id = ? ORDER BY read_on, reading_idx, id",
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Writing to memory can't fail.
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["date", "list", "book", "chapter"])
+        .expect("writable buffer");
+    for row in rows {
+        // Lists are numbered from 1 like on the readings page, removed lists are left empty.
+        let list = match row.reading_idx {
+            idx if idx >= 0 => (idx + 1).to_string(),
+            _ => String::new(),
+        };
+        writer
+            .write_record([
+                row.read_on.to_string(),
+                list,
+                row.book,
+                row.chapter.to_string(),
+            ])
+            .expect("writable buffer");
+    }
+    Ok(writer.into_inner().expect("writable buffer"))
+}
+
+/// `Content-Disposition` of a download, dated so that repeated exports don't clash.
+fn attachment(name: &str, extension: &str) -> String {
+    format!(
+        "attachment; filename=\"brp-{name}-{}.{extension}\"",
+        Utc::now().format("%Y-%m-%d")
+    )
+}
+
+pub async fn get_export(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let archive = Archive::from_user(&state.db, user.id).await?;
+            let body = serde_json::to_string_pretty(&archive).expect("serializable archive");
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "application/json".to_string()),
+                    (header::CONTENT_DISPOSITION, attachment("export", "json")),
+                ],
+                body,
+            )
+                .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+pub async fn get_reading_log(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let body = reading_log_csv(&state.db, user.id).await?;
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        attachment("reading-log", "csv"),
+                    ),
+                ],
+                body,
+            )
+                .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+/// Restore an uploaded archive, replacing all data of the user.
+pub async fn post_import(
+    State(state): State<AppState>,
+    user: Option<User>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let mut upload = None;
+            while let Ok(Some(field)) = multipart.next_field().await {
+                if field.name() == Some("archive") {
+                    upload = field.bytes().await.ok();
+                }
+            }
+            let Some(upload) = upload.filter(|upload| !upload.is_empty()) else {
+                return Ok(fragment_import_message(
+                    Some(Err(locale.t("Choose an export file to import."))),
+                    locale,
+                ));
+            };
+
+            let archive = match Archive::parse(&upload) {
+                Ok(archive) => archive,
+                Err(e) => {
+                    return Ok(fragment_import_message(Some(Err(&e.to_string())), locale));
+                }
+            };
+            if let Err(e) = archive.restore(&state.db, user.id).await {
+                tracing::error!("restoring archive: {e}");
+                return Ok(fragment_import_message(Some(Err(&e.to_string())), locale));
+            }
+            Ok(fragment_import_message(Some(Ok(())), locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// `Err` messages are shown as validation errors of the upload.
+pub fn fragment_import_message(msg: Option<Result<(), &str>>, locale: Locale) -> Markup {
+    html! {
+        @match msg {
+            Some(Ok(())) => p id="import-message" class="text-sm text-green-600" {
+                (locale.t("Imported.")) " "
+                a href="/" class="underline" { (locale.t("Back to readings")) }
+            },
+            Some(Err(msg)) => p id="import-message" class="text-sm text-destructive" {
+                (locale.t("The file could not be imported:")) " " (msg)
+            },
+            None => p id="import-message" class="text-sm invisible" { "message placeholder" },
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use super::books::Book;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// OSIS book identifiers in canonical order.
+const OSIS_IDS: [&str; 66] = [
+    "Gen", "Exod", "Lev", "Num", "Deut", "Josh", "Judg", "Ruth", "1Sam", "2Sam", "1Kgs", "2Kgs",
+    "1Chr", "2Chr", "Ezra", "Neh", "Esth", "Job", "Ps", "Prov", "Eccl", "Song", "Isa", "Jer",
+    "Lam", "Ezek", "Dan", "Hos", "Joel", "Amos", "Obad", "Jonah", "Mic", "Nah", "Hab", "Zeph",
+    "Hag", "Zech", "Mal", "Matt", "Mark", "Luke", "John", "Acts", "Rom", "1Cor", "2Cor", "Gal",
+    "Eph", "Phil", "Col", "1Thess", "2Thess", "1Tim", "2Tim", "Titus", "Phlm", "Heb", "Jas",
+    "1Pet", "2Pet", "1John", "2John", "3John", "Jude", "Rev",
+];
+
+/// USFM `\id` book codes in canonical order.
+const USFM_IDS: [&str; 66] = [
+    "GEN", "EXO", "LEV", "NUM", "DEU", "JOS", "JDG", "RUT", "1SA", "2SA", "1KI", "2KI", "1CH",
+    "2CH", "EZR", "NEH", "EST", "JOB", "PSA", "PRO", "ECC", "SNG", "ISA", "JER", "LAM", "EZK",
+    "DAN", "HOS", "JOL", "AMO", "OBA", "JON", "MIC", "NAM", "HAB", "ZEP", "HAG", "ZEC", "MAL",
+    "MAT", "MRK", "LUK", "JHN", "ACT", "ROM", "1CO", "2CO", "GAL", "EPH", "PHP", "COL", "1TH",
+    "2TH", "1TI", "2TI", "TIT", "PHM", "HEB", "JAS", "1PE", "2PE", "1JN", "2JN", "3JN", "JUD",
+    "REV",
+];
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Unknown format \"{0}\", expected osis, usfm or zefania")]
+    UnknownFormat(String),
+
+    #[error("XML error: {0}")]
+    XmlError(#[from] roxmltree::Error),
+
+    #[error("Unknown book \"{0}\"")]
+    UnknownBook(String),
+
+    #[error("Malformed input: {0}")]
+    Malformed(String),
+
+    #[error("Error storing verses: {0}")]
+    StoreError(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A whole translation in one OSIS XML document.
+    Osis,
+    /// One file per book.
+    Usfm,
+    /// A whole translation in one Zefania XML document.
+    Zefania,
+}
+
+impl FromStr for Format {
+    type Err = ImportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "osis" => Ok(Format::Osis),
+            "usfm" => Ok(Format::Usfm),
+            "zefania" => Ok(Format::Zefania),
+            _ => Err(ImportError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedVerse {
+    pub book: Book,
+    pub chapter: u32,
+    pub verse: u32,
+    /// Section heading shown above the verse.
+    pub title: Option<String>,
+    pub text: String,
+}
+
+pub fn parse(format: Format, input: &str) -> Result<Vec<ImportedVerse>, ImportError> {
+    match format {
+        Format::Osis => parse_osis(input),
+        Format::Usfm => parse_usfm(input),
+        Format::Zefania => parse_zefania(input),
+    }
+}
+
+/// Replace the stored text of `code` with `verses`.
+pub async fn store_translation(
+    pool: &SqlitePool,
+    code: &str,
+    name: &str,
+    verses: &[ImportedVerse],
+) -> Result<(), ImportError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "INSERT INTO translations (code, name) VALUES (?1, ?2)
+        ON CONFLICT (code) DO UPDATE SET name = excluded.name",
+        code,
+        name
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!("DELETE FROM verses WHERE translation = ?", code)
+        .execute(&mut *tx)
+        .await?;
+
+    for v in verses {
+        let book = v.book.index() as i64;
+        sqlx::query!(
+            "INSERT INTO verses (translation, book, chapter, verse, title, text)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (translation, book, chapter, verse)
+            DO UPDATE SET title = excluded.title, text = excluded.text",
+            code,
+            book,
+            v.chapter,
+            v.verse,
+            v.title,
+            v.text
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+fn book_from_id(ids: &[&str; 66], id: &str) -> Result<Book, ImportError> {
+    ids.iter()
+        .position(|i| i.eq_ignore_ascii_case(id))
+        .and_then(|i| Book::from_index(i + 1))
+        .ok_or_else(|| ImportError::UnknownBook(id.to_string()))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Collects verse text while walking a document, with headings held back until
+/// the verse they precede starts.
+#[derive(Default)]
+struct VerseCollector {
+    verses: Vec<ImportedVerse>,
+    current: Option<ImportedVerse>,
+    pending_title: Option<String>,
+}
+
+impl VerseCollector {
+    fn start(&mut self, book: Book, chapter: u32, verse: u32) {
+        self.finish();
+        self.current = Some(ImportedVerse {
+            book,
+            chapter,
+            verse,
+            title: self.pending_title.take(),
+            text: String::new(),
+        });
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some(current) = self.current.as_mut() {
+            current.text.push_str(text);
+        }
+    }
+
+    fn title(&mut self, title: &str) {
+        let title = collapse_whitespace(title);
+        if !title.is_empty() {
+            self.pending_title = Some(title);
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(mut verse) = self.current.take() {
+            verse.text = collapse_whitespace(&verse.text);
+            self.verses.push(verse);
+        }
+    }
+
+    fn into_verses(mut self) -> Vec<ImportedVerse> {
+        self.finish();
+        self.verses
+    }
+}
+
+/// Text of the element, leaving out notes.
+fn element_text(node: roxmltree::Node, skip: &[&str]) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter(|n| {
+            !n.ancestors()
+                .any(|a| a.is_element() && skip.contains(&a.tag_name().name()))
+        })
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+/// Parse "Gen.1.1" (or the first of "Gen.1.1 Gen.1.2" for joined verses).
+fn parse_osis_ref(osis_id: &str) -> Result<(Book, u32, u32), ImportError> {
+    let first = osis_id.split_whitespace().next().unwrap_or_default();
+    let mut parts = first.split('.');
+    let malformed = || ImportError::Malformed(format!("verse reference \"{osis_id}\""));
+    let book = book_from_id(&OSIS_IDS, parts.next().ok_or_else(malformed)?)?;
+    let chapter = parts
+        .next()
+        .and_then(|c| c.parse().ok())
+        .ok_or_else(malformed)?;
+    let verse = parts
+        .next()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(malformed)?;
+    Ok((book, chapter, verse))
+}
+
+/// Handles both verse containers and `sID`/`eID` milestones.
+pub fn parse_osis(input: &str) -> Result<Vec<ImportedVerse>, ImportError> {
+    fn walk(node: roxmltree::Node, out: &mut VerseCollector) -> Result<(), ImportError> {
+        for child in node.children() {
+            if child.is_text() {
+                out.push_text(child.text().unwrap_or_default());
+                continue;
+            }
+            if !child.is_element() {
+                continue;
+            }
+            match child.tag_name().name() {
+                "verse" if child.has_attribute("eID") => out.finish(),
+                "verse" => {
+                    let id = child
+                        .attribute("osisID")
+                        .or(child.attribute("sID"))
+                        .ok_or_else(|| ImportError::Malformed("verse without osisID".into()))?;
+                    let (book, chapter, verse) = parse_osis_ref(id)?;
+                    out.start(book, chapter, verse);
+                    if !child.has_attribute("sID") {
+                        walk(child, out)?;
+                        out.finish();
+                    }
+                }
+                // Book titles and running heads aren't section headings.
+                "title" if matches!(child.attribute("type"), Some("main" | "runningHead")) => {}
+                "title" => out.title(&element_text(child, &["note"])),
+                "header" | "note" | "rdg" => {}
+                _ => walk(child, out)?,
+            }
+        }
+        Ok(())
+    }
+
+    let doc = roxmltree::Document::parse(input)?;
+    let mut out = VerseCollector::default();
+    walk(doc.root(), &mut out)?;
+    Ok(out.into_verses())
+}
+
+/// XMLBIBLE > BIBLEBOOK[bnumber] > CHAPTER[cnumber] > VERS[vnumber], with CAPTION headings.
+pub fn parse_zefania(input: &str) -> Result<Vec<ImportedVerse>, ImportError> {
+    let doc = roxmltree::Document::parse(input)?;
+    let number = |node: roxmltree::Node, attr: &str| -> Result<u32, ImportError> {
+        node.attribute(attr)
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| ImportError::Malformed(format!("missing {attr}")))
+    };
+
+    let mut out = VerseCollector::default();
+    for book_node in doc.descendants().filter(|n| n.has_tag_name("BIBLEBOOK")) {
+        let bnumber = number(book_node, "bnumber")?;
+        let book = Book::from_index(bnumber as usize)
+            .ok_or_else(|| ImportError::UnknownBook(bnumber.to_string()))?;
+        for chapter_node in book_node.children().filter(|n| n.has_tag_name("CHAPTER")) {
+            let chapter = number(chapter_node, "cnumber")?;
+            for node in chapter_node.children().filter(|n| n.is_element()) {
+                match node.tag_name().name() {
+                    "CAPTION" => out.title(&element_text(node, &["NOTE"])),
+                    "VERS" => {
+                        out.start(book.clone(), chapter, number(node, "vnumber")?);
+                        out.push_text(&element_text(node, &["NOTE", "XREF"]));
+                        out.finish();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(out.into_verses())
+}
+
+/// Remove `\f ...\f*` footnotes and `\x ...\x*` cross references.
+fn strip_usfm_notes(input: &str) -> String {
+    let mut text = input.to_string();
+    for (open, close) in [("\\f ", "\\f*"), ("\\fe ", "\\fe*"), ("\\x ", "\\x*")] {
+        while let Some(start) = text.find(open) {
+            let end = text[start..]
+                .find(close)
+                .map_or(text.len(), |end| start + end + close.len());
+            text.replace_range(start..end, "");
+        }
+    }
+    text
+}
+
+/// Parse one USFM book. Markers that carry verse text (paragraphs, poetry, word-level
+/// character styles) are flattened; introductions and running headers are dropped.
+pub fn parse_usfm(input: &str) -> Result<Vec<ImportedVerse>, ImportError> {
+    let text = strip_usfm_notes(input);
+    let mut out = VerseCollector::default();
+    let mut book = None;
+    let mut chapter = 0;
+
+    for piece in text.split('\\').skip(1) {
+        let marker_len = piece
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(piece.len());
+        let (marker, rest) = piece.split_at(marker_len);
+        let (closing, rest) = match rest.strip_prefix('*') {
+            Some(rest) => (true, rest),
+            None => (false, rest.strip_prefix(' ').unwrap_or(rest)),
+        };
+        if closing {
+            out.push_text(rest);
+            continue;
+        }
+
+        let base = marker.trim_end_matches(|c: char| c.is_ascii_digit());
+        match base {
+            "id" => {
+                out.finish();
+                let code = rest.split_whitespace().next().unwrap_or_default();
+                book = Some(book_from_id(&USFM_IDS, code)?);
+            }
+            "c" => {
+                out.finish();
+                chapter = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|c| c.parse().ok())
+                    .ok_or_else(|| ImportError::Malformed(format!("chapter \"{rest}\"")))?;
+            }
+            "v" => {
+                let book = book
+                    .clone()
+                    .ok_or_else(|| ImportError::Malformed("verse before \\id".into()))?;
+                let (number, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                // Verse bridges like "1-2" are stored under their first verse.
+                let verse = number
+                    .split('-')
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| ImportError::Malformed(format!("verse \"{number}\"")))?;
+                out.start(book, chapter, verse);
+                out.push_text(text);
+            }
+            "s" | "d" | "ms" | "qa" => {
+                out.finish();
+                out.title(rest);
+            }
+            "ide" | "h" | "toc" | "toca" | "mt" | "mte" | "imt" | "is" | "ip" | "ipi" | "im"
+            | "io" | "iot" | "ior" | "iex" | "ie" | "rem" | "sts" | "usfm" | "cl" | "cp" | "r"
+            | "mr" | "sr" | "periph" => {}
+            // Word-level attributes: \w grace|strong="G5485"\w*
+            "w" => out.push_text(rest.split('|').next().unwrap_or_default()),
+            _ => out.push_text(rest),
+        }
+    }
+
+    if book.is_none() {
+        return Err(ImportError::Malformed("missing \\id".into()));
+    }
+    Ok(out.into_verses())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verse(
+        book: Book,
+        chapter: u32,
+        verse: u32,
+        title: Option<&str>,
+        text: &str,
+    ) -> ImportedVerse {
+        ImportedVerse {
+            book,
+            chapter,
+            verse,
+            title: title.map(str::to_string),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn osis_verse_containers() {
+        let input = r#"<osis><osisText>
+            <header><work osisWork="test"/></header>
+            <div type="book" osisID="Gen">
+                <title type="main">Genesis</title>
+                <chapter osisID="Gen.1">
+                    <title>The Creation</title>
+                    <verse osisID="Gen.1.1">In the beginning
+                        God created<note>a note</note> the heaven and the earth.</verse>
+                    <verse osisID="Gen.1.2 Gen.1.3">And the earth was without form.</verse>
+                </chapter>
+            </div>
+        </osisText></osis>"#;
+        assert_eq!(
+            parse_osis(input).unwrap(),
+            vec![
+                verse(
+                    Book::Genesis,
+                    1,
+                    1,
+                    Some("The Creation"),
+                    "In the beginning God created the heaven and the earth."
+                ),
+                verse(Book::Genesis, 1, 2, None, "And the earth was without form."),
+            ]
+        );
+    }
+
+    #[test]
+    fn osis_milestones() {
+        let input = r#"<osis><osisText><div type="book" osisID="John">
+            <chapter sID="John.3"/>
+            <p><verse sID="John.3.16" osisID="John.3.16"/>For God so loved
+            the world.<verse eID="John.3.16"/></p>
+            <p><verse sID="John.3.17" osisID="John.3.17"/>For God sent not his
+            Son.<verse eID="John.3.17"/></p>
+            <chapter eID="John.3"/>
+        </div></osisText></osis>"#;
+        assert_eq!(
+            parse_osis(input).unwrap(),
+            vec![
+                verse(Book::John, 3, 16, None, "For God so loved the world."),
+                verse(Book::John, 3, 17, None, "For God sent not his Son."),
+            ]
+        );
+    }
+
+    #[test]
+    fn osis_errors() {
+        assert!(matches!(
+            parse_osis(r#"<osis><verse osisID="Foo.1.1">x</verse></osis>"#),
+            Err(ImportError::UnknownBook(_))
+        ));
+        assert!(matches!(
+            parse_osis(r#"<osis><verse osisID="Gen.1">x</verse></osis>"#),
+            Err(ImportError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_osis("<osis>"),
+            Err(ImportError::XmlError(_))
+        ));
+    }
+
+    #[test]
+    fn usfm_book() {
+        let input = "\\id PSA Test\n\\h Psalms\n\\mt1 Psalms\n\\c 23\n\\d A Psalm of David.\n\
+            \\q1 \\v 1 The \\w Lord|strong=\"H3068\"\\w* is my shepherd;\n\
+            \\q2 I shall not want.\\f + \\fr 23:1 \\ft a footnote\\f*\n\
+            \\v 2-3 He maketh me to lie down.\\x - \\xo 23:2 \\xt Ezek 34:14\\x*\n";
+        assert_eq!(
+            parse_usfm(input).unwrap(),
+            vec![
+                verse(
+                    Book::Psalms,
+                    23,
+                    1,
+                    Some("A Psalm of David."),
+                    "The Lord is my shepherd; I shall not want."
+                ),
+                verse(Book::Psalms, 23, 2, None, "He maketh me to lie down."),
+            ]
+        );
+    }
+
+    #[test]
+    fn usfm_errors() {
+        assert!(matches!(
+            parse_usfm("\\c 1\n\\v 1 text"),
+            Err(ImportError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_usfm("\\id XYZ\n\\c 1\n\\v 1 text"),
+            Err(ImportError::UnknownBook(_))
+        ));
+        assert!(matches!(
+            parse_usfm("\\id GEN\n\\c one\n"),
+            Err(ImportError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn zefania_bible() {
+        let input = r#"<XMLBIBLE biblename="Test">
+            <BIBLEBOOK bnumber="1" bname="Genesis">
+                <CHAPTER cnumber="1">
+                    <CAPTION>The Creation</CAPTION>
+                    <VERS vnumber="1">In the beginning <gr str="430">God</gr> created<NOTE>a note</NOTE>
+                        the heaven.</VERS>
+                    <VERS vnumber="2">And the earth.</VERS>
+                </CHAPTER>
+            </BIBLEBOOK>
+            <BIBLEBOOK bnumber="66" bname="Revelation">
+                <CHAPTER cnumber="22"><VERS vnumber="21">Amen.</VERS></CHAPTER>
+            </BIBLEBOOK>
+        </XMLBIBLE>"#;
+        assert_eq!(
+            parse_zefania(input).unwrap(),
+            vec![
+                verse(
+                    Book::Genesis,
+                    1,
+                    1,
+                    Some("The Creation"),
+                    "In the beginning God created the heaven."
+                ),
+                verse(Book::Genesis, 1, 2, None, "And the earth."),
+                verse(Book::Revelation, 22, 21, None, "Amen."),
+            ]
+        );
+    }
+
+    #[test]
+    fn zefania_errors() {
+        assert!(matches!(
+            parse_zefania(r#"<XMLBIBLE><BIBLEBOOK bnumber="67"/></XMLBIBLE>"#),
+            Err(ImportError::UnknownBook(_))
+        ));
+        assert!(matches!(
+            parse_zefania(r#"<XMLBIBLE><BIBLEBOOK bnumber="1"><CHAPTER/></BIBLEBOOK></XMLBIBLE>"#),
+            Err(ImportError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert_eq!("OSIS".parse::<Format>().unwrap(), Format::Osis);
+        assert!("epub".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use super::{
+    model::UserSettings,
+    search::{fts_query, highlight_snippet, MATCH_END, MATCH_START},
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        self,
+        hx::HxCfg,
+        markdown::markdown,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg, ButtonType},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Form,
+};
+use chrono::NaiveDate;
+use maud::{html, Markup};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+/// What a user wrote about the reading of a day, in Markdown.
+#[derive(Debug)]
+pub struct JournalEntry {
+    /// The reading date, see `UserDates::reading_date`.
+    pub date: NaiveDate,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct JournalHit {
+    pub date: NaiveDate,
+    /// Text around the match, with the matched terms between `MATCH_START` and `MATCH_END`.
+    pub snippet: String,
+}
+
+impl JournalEntry {
+    pub async fn from_user_date(
+        pool: &SqlitePool,
+        user_id: i64,
+        date: NaiveDate,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let rec = sqlx::query!(
+            "SELECT entry_date, body FROM journal WHERE user_id = ? AND entry_date = ?",
+            user_id,
+            date
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rec.map(|rec| Self {
+            date: rec.entry_date,
+            body: rec.body,
+        }))
+    }
+
+    /// Every entry of the user, newest first.
+    pub async fn all_from_user(pool: &SqlitePool, user_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        Ok(sqlx::query!(
+            "SELECT entry_date, body FROM journal WHERE user_id = ? ORDER BY entry_date DESC",
+            user_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|rec| Self {
+            date: rec.entry_date,
+            body: rec.body,
+        })
+        .collect())
+    }
+
+    /// Save the entry of the date. An empty body deletes it.
+    pub async fn save(
+        pool: &SqlitePool,
+        user_id: i64,
+        date: NaiveDate,
+        body: &str,
+    ) -> Result<(), sqlx::Error> {
+        if body.trim().is_empty() {
+            sqlx::query!(
+                "DELETE FROM journal WHERE user_id = ? AND entry_date = ?",
+                user_id,
+                date
+            )
+            .execute(pool)
+            .await?;
+        } else {
+            sqlx::query!(
+                "INSERT INTO journal (user_id, entry_date, body) VALUES (?1, ?2, ?3)
+                ON CONFLICT (user_id, entry_date) DO UPDATE
+                SET body = excluded.body, updated_at = CURRENT_TIMESTAMP",
+                user_id,
+                date,
+                body
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Entries of the user matching the input, best first.
+pub async fn search_journal(
+    pool: &SqlitePool,
+    user_id: i64,
+    input: &str,
+) -> Result<Vec<JournalHit>, sqlx::Error> {
+    let Some(query) = fts_query(input) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(sqlx::query!(
+        r#"SELECT j.entry_date,
+            snippet(journal_fts, 0, ?1, ?2, '…', 32) AS "snippet!: String"
+        FROM journal_fts
+        JOIN journal j ON j.id = journal_fts.rowid
+        WHERE journal_fts MATCH ?3 AND j.user_id = ?4
+        ORDER BY rank"#,
+        MATCH_START,
+        MATCH_END,
+        query,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|hit| JournalHit {
+        date: hit.entry_date,
+        snippet: hit.snippet,
+    })
+    .collect())
+}
+
+/// Journal editor of the reading date in the readings sidebar.
+pub fn fragment_journal(
+    date: NaiveDate,
+    entry: Option<&JournalEntry>,
+    saved: bool,
+    locale: Locale,
+) -> Markup {
+    html! {
+        div id="journal" class="w-full flex flex-col gap-2 mt-4" {
+            div class="flex justify-between items-baseline" {
+                h2 class="font-bold text-md" { (locale.t("Journal")) }
+                a href="/journal" class="text-xs text-foreground/60 hover:underline" { (locale.t("History")) }
+            }
+            form class="flex flex-col gap-2" hx-post="/journal" hx-target="#journal" hx-swap="outerHTML" {
+                input type="hidden" name="date" value=(date.format("%Y-%m-%d"));
+                textarea name="body" rows="4"
+                    placeholder=(locale.t("What struck you in today's reading?"))
+                    class="text-sm rounded-xs border-border bg-background"
+                {
+                    @if let Some(entry) = entry { (entry.body) }
+                }
+                div class="flex justify-between items-center" {
+                    span class="text-xs text-green-600" {
+                        @if saved { (locale.t("Saved")) }
+                    }
+                    (ui_button(html! { (locale.t("Save")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).with_type(ButtonType::Submit),
+                        &HxCfg::new()
+                    ))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JournalRequest {
+    date: NaiveDate,
+    body: String,
+}
+
+pub async fn post_journal(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<JournalRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            JournalEntry::save(&state.db, user.id, form.date, &form.body).await?;
+            let entry = JournalEntry::from_user_date(&state.db, user.id, form.date).await?;
+            Ok(fragment_journal(form.date, entry.as_ref(), true, locale))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JournalQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// All entries, or those matching `q`. Only the entries are rendered when
+/// requested by htmx.
+pub async fn get_journal(
+    State(state): State<AppState>,
+    user: Option<User>,
+    headers: HeaderMap,
+    Query(query): Query<JournalQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let entries = if query.q.trim().is_empty() {
+                Entries::All(JournalEntry::all_from_user(&state.db, user.id).await?)
+            } else {
+                Entries::Hits(search_journal(&state.db, user.id, &query.q).await?)
+            };
+
+            let content = fragment_entries(&entries, locale);
+            if headers.contains_key("HX-Request") {
+                Ok(content)
+            } else {
+                Ok(view::pages::page(
+                    locale.t("Journal"),
+                    page(&query.q, content, locale),
+                ))
+            }
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+enum Entries {
+    All(Vec<JournalEntry>),
+    Hits(Vec<JournalHit>),
+}
+
+fn page(q: &str, entries: Markup, locale: Locale) -> Markup {
+    html! {
+        div class="flex justify-center" {
+            div class="flex flex-col gap-8 w-[900px] py-8 px-4" {
+                div class="flex justify-between items-center" {
+                    h1 class="text-xl font-bold" { (locale.t("Journal")) }
+                    (ui_button(html! { (locale.t("Back to readings")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).as_link("/"),
+                        &HxCfg::new()
+                    ))
+                }
+                form
+                    hx-get="/journal"
+                    hx-trigger="input changed delay:300ms from:find input, submit"
+                    hx-target="#journal-entries"
+                    hx-swap="outerHTML"
+                {
+                    input type="search" name="q" value=(q) placeholder=(locale.t("Search your journal…"))
+                        class="w-full text-sm rounded-xs border-border bg-background py-1.5";
+                }
+                (entries)
+            }
+        }
+    }
+}
+
+fn fragment_entries(entries: &Entries, locale: Locale) -> Markup {
+    html! {
+        div id="journal-entries" class="flex flex-col gap-6" {
+            @match entries {
+                Entries::All(entries) if entries.is_empty() => {
+                    p class="text-sm text-foreground/60" { (locale.t("No journal entries yet.")) }
+                }
+                Entries::All(entries) => {
+                    @for entry in entries {
+                        article id=(format!("entry-{}", entry.date.format("%Y-%m-%d"))) {
+                            h2 class="font-bold text-md mb-1" { (locale.long_date(entry.date)) }
+                            div class="prose prose-sm text-sm" { (markdown(&entry.body)) }
+                        }
+                    }
+                }
+                Entries::Hits(hits) if hits.is_empty() => {
+                    p class="text-sm text-foreground/60" { (locale.t("No entries found.")) }
+                }
+                Entries::Hits(hits) => {
+                    @for hit in hits {
+                        a href=(format!("/journal#entry-{}", hit.date.format("%Y-%m-%d")))
+                            class="block p-2 rounded-xs hover:bg-foreground/5"
+                        {
+                            div class="text-sm font-semibold" { (locale.long_date(hit.date)) }
+                            div class="text-sm" { (highlight_snippet(&hit.snippet)) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
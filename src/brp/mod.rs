@@ -1,19 +1,27 @@
-use self::model::UserDates;
+use self::{
+    annotations::{Annotations, ChapterContext, HighlightColor},
+    journal::{fragment_journal, JournalEntry},
+    model::{UserDates, UserProgress, UserSettings},
+};
 use crate::{
     auth::User,
     brp::{
-        books::{get_day_plan, Book},
-        content::{ChapterDispatcher, ChapterError, IndonesianBible},
+        books::{Book, Passage},
+        content::{
+            align_verses, Bible, BibleRegistry, ChapterDispatcher, ChapterError, Translation, Verse,
+        },
         model::UserReadings,
     },
     errors::ApiError,
+    i18n::Locale,
     utils::today_naive_date,
     view::{
         self,
         hx::HxCfg,
+        markdown::markdown,
         pages::login::redirect_login,
         ui::{
-            button::{ui_button, ButtonCfg},
+            button::{ui_button, ButtonCfg, ButtonType},
             datepicker::{ui_datepicker, DatePickerCfgBuilder},
             modal::{ui_modal, ModalCfg},
             Color,
@@ -22,33 +30,77 @@ use crate::{
     AppState,
 };
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::HeaderMap,
     response::IntoResponse,
     Form,
 };
-use chrono::{Datelike, Duration, NaiveDate};
+use chrono::{Datelike, NaiveDate};
 use maud::{html, Markup};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
 
+pub mod annotations;
 pub mod books;
+pub mod calendar;
 pub mod content;
+pub mod export;
+pub mod import;
+pub mod journal;
 pub mod model;
+pub mod onboarding;
+pub mod plan;
+pub mod reference;
+pub mod schedule;
+pub mod search;
+pub mod settings;
+pub mod stats;
 
+/// The readings page. Opens on the first reading list, or on the passage in the
+/// query, e.g. when following a link from "My notes".
 pub async fn page_brp(
     State(state): State<AppState>,
     user: Option<User>,
+    passage: Option<Query<PassageQuery>>,
 ) -> Result<impl IntoResponse, ApiError> {
     match user {
         Some(user) => {
             let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
             tracing::trace!("dates: {:?}", dates);
 
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
             let readings = UserReadings::from_user(&state.db, user.id).await;
             tracing::trace!("readings: {:?}", readings);
+            if readings.readings.is_empty() {
+                return Ok(view::pages::page(
+                    settings.locale.t("Welcome"),
+                    onboarding::page_onboarding(settings.locale),
+                ));
+            }
+
+            let progress =
+                UserProgress::from_user(&state.db, user.id, dates.reading_date()).await?;
+            let (portion, focus) = match passage.as_ref().and_then(|Query(q)| q.passage()) {
+                Some(passage) => {
+                    let focus = passage.verse.map(|verse| (passage.chapter, verse));
+                    (vec![passage], focus)
+                }
+                None => (readings.readings[0].portion(&dates), None),
+            };
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
+            let journal =
+                JournalEntry::from_user_date(&state.db, user.id, dates.reading_date()).await?;
             Ok(view::pages::page(
                 "Index",
-                page(user, readings, dates.start_date, dates.offset).await?,
+                page(
+                    user,
+                    &state.bibles,
+                    settings,
+                    readings,
+                    progress,
+                    (dates, journal),
+                    (&portion, &annotations, focus),
+                )
+                .await?,
             ))
         }
         None => Ok(redirect_login()),
@@ -57,13 +109,14 @@ pub async fn page_brp(
 
 async fn page(
     _profile: User,
+    bibles: &BibleRegistry,
+    settings: UserSettings,
     readings: UserReadings,
-    start_date: NaiveDate,
-    offset: i64,
+    progress: UserProgress,
+    (dates, journal): (UserDates, Option<JournalEntry>),
+    (portion, annotations, focus): (&[Passage], &Annotations, Option<(i64, u32)>),
 ) -> Result<Markup, ApiError> {
-    let reading_date = today_naive_date() + Duration::days(offset);
-    let day_diff = (reading_date - start_date).num_days() + 1;
-
+    let locale = settings.locale;
     Ok(html! {
         div class="flex justify-center" {
             div class="flex flex-col justify-between items-center h-screen gap-2 pt-4 px-4 pb-4 min-w-60 border border-border shadow shadow-foreground/10 shadow-md" {
@@ -73,17 +126,17 @@ async fn page(
                         id="date-form"
                         hx-target="#readings"
                         hx-include="[name='reading_idx']"
-                        hx-select-oob="#chapter-content"
+                        hx-select-oob="#chapter-content, #journal"
                     {
                         div class="flex flex-col gap-1"{
                             label for="start-date-picker"
                                 class="font-semibold text-sm"
-                                { "Start date" }
+                                { (locale.t("Start date")) }
                             (ui_datepicker(
                                 DatePickerCfgBuilder::new()
                                     .with_id("start-date-picker")
                                     .with_name("start_date")
-                                    .with_value(&fmt_naivedate(start_date))
+                                    .with_value(&fmt_naivedate(dates.start_date))
                                     .with_script("on changeDate js htmx.trigger('#date-form', 'submit') end")
                                     .build()
                             ))
@@ -91,23 +144,86 @@ async fn page(
                         div class="flex flex-col gap-1 mt-4"{
                             label for="current-date-picker"
                                 class="font-semibold text-sm"
-                                { "Currently reading" }
+                                { (locale.t("Currently reading")) }
                             (ui_datepicker(
                                 DatePickerCfgBuilder::new()
                                     .with_id("current-date-picker")
                                     .with_name("date")
                                     .with_script("on changeDate js htmx.trigger('#date-form', 'submit') end")
-                                    .with_value(&fmt_naivedate(reading_date))
+                                    .with_value(&fmt_naivedate(dates.reading_date()))
                                     .build()
                             ))
                         }
                     }
-                    (fragment_readings_rows(&readings, day_diff, Some(0)))
+                    (fragment_readings_rows(&readings, &progress, &dates, Some(0), locale))
+                    (fragment_journal(dates.reading_date(), journal.as_ref(), false, locale))
+                }
+
+                div class="flex flex-col gap-2 w-full" {
+                    form class="flex gap-2"
+                        hx-get="/goto"
+                        hx-target="#chapter-content"
+                        hx-swap="outerHTML"
+                    {
+                        input type="text" name="ref" placeholder=(locale.t("Go to, e.g. Yoh 3:16-18; Rm 8")) required
+                            class="flex-1 min-w-0 text-sm rounded-xs border-border bg-background py-1.5";
+                        (ui_button(html! { (locale.t("Go")) },
+                            &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit),
+                            &HxCfg::new()
+                        ))
+                    }
+                    (ui_button(html!{
+                            span { (locale.t("Search")) }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(Color::Alternative)
+                            .with_cn("w-full"),
+                        &HxCfg::new()
+                            .with_get("/search")
+                            .with_target("#chapter-content")
+                            .with_swap("outerHTML")
+                    ))
+                    (ui_button(html!{
+                            span { (locale.t("My notes")) }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(Color::Alternative)
+                            .with_cn("w-full")
+                            .as_link("/notes"),
+                        &HxCfg::new()
+                    ))
+                    (ui_button(html!{
+                            span { (locale.t("Journal")) }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(Color::Alternative)
+                            .with_cn("w-full")
+                            .as_link("/journal"),
+                        &HxCfg::new()
+                    ))
+                    (ui_button(html!{
+                            span { (locale.t("Statistics")) }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(Color::Alternative)
+                            .with_cn("w-full")
+                            .as_link("/stats"),
+                        &HxCfg::new()
+                    ))
+                    (ui_button(html!{
+                            span { (locale.t("Settings")) }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(Color::Alternative)
+                            .with_cn("w-full")
+                            .as_link("/settings"),
+                        &HxCfg::new()
+                    ))
                 }
 
                 div class="flex gap-2 bg-red-100 w-full" {
                     (ui_button(html!{
-                            span { "Logout" }
+                            span { (locale.t("Logout")) }
                         },
                         &ButtonCfg::new()
                             .with_color(Color::Default)
@@ -118,38 +234,470 @@ async fn page(
                 }
             }
 
-            @let info = get_day_plan(readings.readings.first().unwrap(), day_diff).0;
-            (fragment_chapter_content(info.book, info.chapter as usize).await?)
+            (fragment_chapter_content(bibles, &settings, portion, annotations, focus).await?)
         }
     })
 }
 
-async fn fragment_chapter_content(book: Book, chapter: usize) -> Result<Markup, ChapterError> {
+/// (translation, parallel translation) chosen by the user.
+fn translations<'a>(
+    bibles: &'a BibleRegistry,
+    settings: &UserSettings,
+) -> (&'a Translation, Option<&'a Translation>) {
+    let translation = bibles.get(settings.translation.as_deref());
+    let parallel = settings
+        .parallel
+        .as_deref()
+        .map(|code| bibles.get(Some(code)))
+        .filter(|p| p.code != translation.code);
+    (translation, parallel)
+}
+
+/// A chapter, or the verses of it in a portion, as shown in the reading pane.
+struct ChapterView {
+    book: Book,
+    chapter: i64,
+    title: String,
+    content: Bible,
+    /// `None` if the parallel translation doesn't have the chapter.
+    other: Option<Bible>,
+    parallel: bool,
+    first: u32,
+    last: Option<u32>,
+}
+
+impl ChapterView {
+    fn id(&self) -> String {
+        format!(
+            "chapter-{}-{}-{}",
+            self.book.index(),
+            self.chapter,
+            self.first
+        )
+    }
+
+    fn in_range(&self, verse: &Verse) -> bool {
+        verse.number >= self.first && verse.number <= self.last.unwrap_or(u32::MAX)
+    }
+}
+
+async fn chapter_view(
+    translation: &Translation,
+    parallel: Option<&Translation>,
+    book: &Book,
+    chapter: i64,
+    (first, last): (u32, Option<u32>),
+    locale: Locale,
+) -> Result<ChapterView, ChapterError> {
+    let content = translation
+        .source
+        .get_chapter(book, chapter as usize)
+        .await?;
+    let other = match parallel {
+        Some(parallel) => match parallel.source.get_chapter(book, chapter as usize).await {
+            Ok(other) => Some(other),
+            // e.g. a New Testament only translation
+            Err(ChapterError::NotFound(..)) => None,
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+    Ok(ChapterView {
+        title: Passage::chapter(book.clone(), chapter).name(locale),
+        book: book.clone(),
+        chapter,
+        content,
+        other,
+        parallel: parallel.is_some(),
+        first,
+        last,
+    })
+}
+
+/// `focus` is a (chapter, verse) to highlight and scroll to.
+async fn fragment_chapter_content(
+    bibles: &BibleRegistry,
+    settings: &UserSettings,
+    portion: &[Passage],
+    annotations: &Annotations,
+    focus: Option<(i64, u32)>,
+) -> Result<Markup, ChapterError> {
     tracing::trace!("fragment_chapter_content");
-    let bib = IndonesianBible.get_chapter(&book, chapter).await?;
-    tracing::trace!("successfully get the bible chapter");
+    let locale = settings.locale;
+    let (translation, parallel) = translations(bibles, settings);
+
+    let mut chapters = Vec::new();
+    for passage in portion {
+        for chapter in passage.chapters() {
+            chapters.push(
+                chapter_view(
+                    translation,
+                    parallel,
+                    &passage.book,
+                    chapter,
+                    passage.verse_range(chapter),
+                    locale,
+                )
+                .await?,
+            );
+        }
+    }
+    tracing::trace!("successfully get the bible chapters");
+
     Ok(html! {
-        div id="chapter-content" class="border border-border bg-background-100 w-[850px] pb-4 px-4 text-wrap max-h-screen h-screen flex flex-col" {
-            div class="flex justify-center font-bold py-2 mt-2"{
-                (bib.title)
+        div id="chapter-content" class=(format!("border border-border bg-background-100 {} pb-4 px-4 text-wrap max-h-screen h-screen flex flex-col", if parallel.is_some() { "w-[1200px]" } else { "w-[850px]" })) {
+            div class="flex justify-between items-center py-2 mt-2 gap-4" {
+                div class="flex-1" {}
+                div class="font-bold" {
+                    @match (portion, chapters.as_slice()) {
+                        (_, []) => { (locale.t("Rest day")) }
+                        ([passage], [view]) if passage.is_whole_chapters() => { (view.title) }
+                        _ => { (portion_label(portion, locale)) }
+                    }
+                }
+                div class="flex-1 flex justify-end gap-2" {
+                    @if bibles.all().len() > 1 {
+                        (fragment_translation_switcher(bibles, &translation.code, parallel.map(|p| p.code.as_str()), locale))
+                    }
+                }
             }
             div class="overflow-y-auto flex-shrink border border-border p-4 scrollbar-thin scrollbar-thumb-foreground/70 scrollbar-track-foreground/10" {
-                @for (idx, ch) in bib.verses.verse.iter().enumerate() {
-                    div class="mb-3" {
-                        @if let Some(ref title) = ch.title {
-                            span class="font-bold pt-3 pb-2 block" { (title) }
+                @if chapters.is_empty() {
+                    p class="text-foreground/60" { (locale.t("Nothing to read in this list today.")) }
+                }
+                @if let Some(parallel) = parallel {
+                    div class="grid grid-cols-2 gap-6 pb-2 mb-2 border-b border-border text-xs text-foreground/60" {
+                        div { (translation.name) }
+                        div { (parallel.name) }
+                    }
+                }
+                @for view in &chapters {
+                    @if chapters.len() > 1 {
+                        h3 class="font-bold text-lg pt-2 pb-3" { (view.title) }
+                    }
+                    (fragment_chapter(view, annotations, focus, locale))
+                }
+            }
+        }
+    })
+}
+
+/// The verses of a chapter with the user's highlights, bookmarks and notes, and
+/// a toolbar to annotate the verses selected by clicking their numbers.
+/// Swapped on its own when the annotations change.
+fn fragment_chapter(
+    view: &ChapterView,
+    annotations: &Annotations,
+    focus: Option<(i64, u32)>,
+    locale: Locale,
+) -> Markup {
+    let t = |text| locale.t(text);
+    let focus = focus
+        .filter(|(chapter, _)| *chapter == view.chapter)
+        .map(|(_, verse)| verse);
+    let verses: Vec<_> = view
+        .content
+        .verses
+        .verse
+        .iter()
+        .filter(|v| view.in_range(v))
+        .collect();
+    let rows = if view.parallel {
+        let others: Vec<_> = view
+            .other
+            .iter()
+            .flat_map(|o| o.verses.verse.iter())
+            .filter(|v| view.in_range(v))
+            .collect();
+        align_verses(&verses, &others)
+    } else {
+        verses.into_iter().map(|v| (Some(v), None)).collect()
+    };
+    let context = serde_json::to_string(&ChapterContext {
+        book: view.book.index(),
+        chapter: view.chapter,
+        first: view.first,
+        last: view.last,
+    })
+    .expect("serializable struct");
+    let target = format!("#{}", view.id());
+
+    html! {
+        div id=(view.id()) x-data=(VERSE_SELECTION) {
+            @for (left, right) in rows {
+                @let Some(number) = left.or(right).map(|v| v.number) else { continue };
+                @let verse = VerseMarks {
+                    focus: Some(number) == focus,
+                    highlight: annotations.highlight(&view.book, view.chapter, number),
+                    bookmark: annotations.bookmark(&view.book, view.chapter, number).is_some(),
+                };
+                @if view.parallel {
+                    div class="grid grid-cols-2 gap-6" {
+                        (fragment_verse(left, &verse, locale))
+                        (fragment_verse(right, &verse, locale))
+                    }
+                } @else {
+                    (fragment_verse(left, &verse, locale))
+                }
+                @for note in annotations.notes_after(&view.book, view.chapter, number) {
+                    @let edit = format!(
+                        "edit({}, {}, {})",
+                        note.verse,
+                        note.end_verse,
+                        serde_json::to_string(&note.body).expect("serializable string")
+                    );
+                    div class="mb-3 ml-6 border-l-2 border-foreground/30 pl-3 text-sm" {
+                        div class="flex justify-between text-xs text-foreground/60" {
+                            span { (note.passage().reference()) }
+                            div class="flex gap-2" {
+                                button type="button" class="hover:underline" x-on:click=(edit) { (t("Edit")) }
+                                button type="button" class="hover:underline text-red-600"
+                                    hx-post=(format!("/annotations/notes/{}/delete", note.id))
+                                    hx-vals=(context)
+                                    hx-target=(target)
+                                    hx-swap="outerHTML"
+                                { (t("Delete")) }
+                            }
+                        }
+                        div class="prose prose-sm" { (markdown(&note.body)) }
+                    }
+                }
+            }
+
+            div x-show="from !== null" x-cloak
+                class="sticky bottom-0 mt-2 p-2 border border-border rounded-sm bg-background-100 shadow"
+            {
+                form class="flex flex-col gap-2" hx-vals=(context) hx-target=(target) hx-swap="outerHTML" {
+                    input type="hidden" name="verse" x-bind:value="from";
+                    input type="hidden" name="end_verse" x-bind:value="to";
+                    div class="flex gap-2 items-center text-sm" {
+                        span class="font-semibold mr-2" { (view.chapter) ":" span x-text="range()" {} }
+                        @for color in HighlightColor::all() {
+                            button type="button" title=(t(color.name()))
+                                class=(format!("w-5 h-5 rounded-full {}", color.swatch()))
+                                hx-post="/annotations/highlight"
+                                hx-vals=(format!(r#"{{"color": "{color}"}}"#))
+                            {}
+                        }
+                        button type="button" class="px-2 py-1 text-xs hover:bg-foreground/5"
+                            hx-post="/annotations/highlight"
+                            hx-vals=r#"{"color": ""}"#
+                        { (t("Clear")) }
+                        button type="button" class="px-2 py-1 text-xs hover:bg-foreground/5" hx-post="/annotations/bookmark" {
+                            (t("Bookmark"))
                         }
-                        div class="text-wrap" {
-                            sup class="mr-1 text-foreground/50 font-extrabold"{ (idx + 1) }
-                            span {
-                                (ch.text)
+                        button type="button" class="px-2 py-1 text-xs hover:bg-foreground/5" x-on:click="noting = !noting" {
+                            (t("Note"))
+                        }
+                        button type="button" class="ml-auto px-2 text-foreground/50" title=(t("Cancel")) x-on:click="clear()" { "×" }
+                    }
+                    div x-show="noting" class="flex flex-col gap-2" {
+                        textarea name="note" rows="3" x-model="text"
+                            placeholder=(t("Write a note, Markdown is supported…"))
+                            class="text-sm rounded-xs border-border bg-background" {}
+                        div {
+                            button type="button"
+                                class="px-3 py-1 text-xs rounded-xs bg-foreground text-background"
+                                hx-post="/annotations/note"
+                            { (t("Save note")) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Client-side state of a chapter block: the selected verses and the note being written.
+const VERSE_SELECTION: &str = r#"{
+    from: null,
+    to: null,
+    noting: false,
+    text: '',
+    pick(n, extend) {
+        if (extend && this.from !== null) {
+            this.from = Math.min(this.from, n);
+            this.to = Math.max(this.to, n);
+        } else if (this.from === n && this.to === n) {
+            this.clear();
+        } else {
+            this.from = n;
+            this.to = n;
+        }
+    },
+    selected(n) {
+        return this.from !== null && n >= this.from && n <= this.to;
+    },
+    range() {
+        return this.from === this.to ? `${this.from}` : `${this.from}-${this.to}`;
+    },
+    edit(from, to, text) {
+        this.from = from;
+        this.to = to;
+        this.text = text;
+        this.noting = true;
+    },
+    clear() {
+        this.from = null;
+        this.to = null;
+        this.noting = false;
+        this.text = '';
+    },
+}"#;
+
+struct VerseMarks {
+    focus: bool,
+    highlight: Option<HighlightColor>,
+    bookmark: bool,
+}
+
+/// A verse with its section heading, or a placeholder for verses the
+/// parallel translation doesn't have.
+fn fragment_verse(verse: Option<&Verse>, marks: &VerseMarks, locale: Locale) -> Markup {
+    let background = match (marks.highlight, marks.focus) {
+        (Some(color), _) => color.class(),
+        (None, true) => "bg-yellow-100",
+        (None, false) => "",
+    };
+    html! {
+        div class=(format!("mb-3 rounded-xs {background}"))
+            _=[marks.focus.then_some("init call me.scrollIntoView({block: 'center'})")]
+        {
+            @match verse {
+                Some(ch) => {
+                    @if let Some(ref title) = ch.title {
+                        span class="font-bold pt-3 pb-2 block" { (title) }
+                    }
+                    div class="text-wrap" x-bind:class=(format!("selected({}) && 'ring-2 ring-blue-400 rounded-xs'", ch.number)) {
+                        sup class="mr-1 text-foreground/50 font-extrabold cursor-pointer hover:text-foreground"
+                            title=(locale.t("Select verse, shift-click to select a range"))
+                            x-on:click=(format!("pick({}, $event.shiftKey)", ch.number))
+                        {
+                            @if marks.bookmark {
+                                span class="text-red-500 mr-0.5" title=(locale.t("Bookmark")) { "●" }
                             }
+                            (ch.number)
+                        }
+                        span {
+                            (ch.text)
                         }
                     }
                 }
+                None => span class="text-foreground/30" { "—" },
             }
         }
-    })
+    }
+}
+
+fn fragment_translation_switcher(
+    bibles: &BibleRegistry,
+    current: &str,
+    parallel: Option<&str>,
+    locale: Locale,
+) -> Markup {
+    let hx_include = "[name='reading_idx'], #chapter-content select";
+    html! {
+        select name="translation"
+            class="text-xs rounded-xs border-border bg-background py-1"
+            hx-post="/translation"
+            hx-trigger="change"
+            hx-include=(hx_include)
+            hx-target="#chapter-content"
+            hx-swap="outerHTML"
+        {
+            @for t in bibles.all() {
+                option value=(t.code) selected[t.code == current] { (t.name) }
+            }
+        }
+        select name="parallel"
+            class="text-xs rounded-xs border-border bg-background py-1"
+            title=(locale.t("Read side by side with another translation"))
+            hx-post="/translation"
+            hx-trigger="change"
+            hx-include=(hx_include)
+            hx-target="#chapter-content"
+            hx-swap="outerHTML"
+        {
+            option value="" selected[parallel.is_none()] { (locale.t("No parallel")) }
+            @for t in bibles.all().iter().filter(|t| t.code != current) {
+                option value=(t.code) selected[Some(t.code.as_str()) == parallel] { "+ " (t.name) }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranslationRequest {
+    translation: String,
+    /// Empty for no parallel translation.
+    #[serde(default)]
+    parallel: String,
+    reading_idx: usize,
+}
+
+/// Switch the user's translations and re-render the chapter pane with them.
+pub async fn post_translation(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<TranslationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let t = |text| settings.locale.t(text);
+            let known = |code: &str| state.bibles.all().iter().any(|t| t.code == code);
+            let parallel =
+                Some(form.parallel.as_str()).filter(|p| !p.is_empty() && *p != form.translation);
+            if !known(&form.translation) || parallel.is_some_and(|p| !known(p)) {
+                return Ok(error_modal(t("Error"), t("Unknown translation")).into_response());
+            }
+            UserSettings::set_translations(&state.db, user.id, &form.translation, parallel).await?;
+
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let readings = UserReadings::from_user(&state.db, user.id).await;
+            let Some(reading) = readings.readings.get(form.reading_idx) else {
+                return Ok(error_modal(t("Error"), t("Unknown reading list")).into_response());
+            };
+            let portion = reading.portion(&dates);
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
+            let settings = UserSettings {
+                translation: Some(form.translation),
+                parallel: parallel.map(str::to_string),
+                ..settings
+            };
+
+            Ok(
+                fragment_chapter_content(&state.bibles, &settings, &portion, &annotations, None)
+                    .await?
+                    .into_response(),
+            )
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+/// (books, reference) of a portion, e.g. ("Genesis", "3-5"), ("Psalms", "119:1-32")
+/// or ("Deuteronomy – Joshua", "34 – 2").
+fn portion_parts(portion: &[Passage], locale: Locale) -> (String, String) {
+    match portion {
+        [] => (locale.t("Rest day").to_string(), String::new()),
+        [passage] => (passage.book.name(locale).to_string(), passage.reference()),
+        [first, .., last] => (
+            format!("{} – {}", first.book.name(locale), last.book.name(locale)),
+            format!("{} – {}", first.chapter, last.end_chapter),
+        ),
+    }
+}
+
+fn portion_label(portion: &[Passage], locale: Locale) -> String {
+    match portion {
+        [] => locale.t("Rest day").to_string(),
+        _ => portion
+            .iter()
+            .map(|p| p.name(locale))
+            .collect::<Vec<_>>()
+            .join("; "),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -179,15 +727,33 @@ pub async fn post_dates(
     match user {
         Some(user) => {
             let offset = (form.date - today_naive_date()).num_days();
-            let diff = (form.date - form.start_date).num_days() + 1;
+            let dates = UserDates {
+                start_date: form.start_date,
+                offset,
+            };
 
             let readings = UserReadings::from_user(&state.db, user.id).await;
-            let info = get_day_plan(&readings.readings[form.reading_idx], diff).0;
+            // The list may be gone since the page was loaded, show the first one then.
+            let reading_idx = if form.reading_idx < readings.readings.len() {
+                form.reading_idx
+            } else {
+                0
+            };
+            let portion = readings
+                .readings
+                .get(reading_idx)
+                .map(|reading| reading.portion(&dates))
+                .unwrap_or_default();
 
             UserDates::set(&state.db, user.id, form.start_date, offset).await;
+            let progress = UserProgress::from_user(&state.db, user.id, form.date).await?;
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
+            let journal = JournalEntry::from_user_date(&state.db, user.id, form.date).await?;
             Ok(html! {
-                (fragment_readings_rows(&readings, diff, Some(form.reading_idx)))
-                (fragment_chapter_content(info.book, info.chapter as usize).await?)
+                (fragment_readings_rows(&readings, &progress, &dates, Some(reading_idx), settings.locale))
+                (fragment_chapter_content(&state.bibles, &settings, &portion, &annotations, None).await?)
+                (fragment_journal(form.date, journal.as_ref(), false, settings.locale))
             })
         }
         None => Ok(redirect_login()),
@@ -196,8 +762,6 @@ pub async fn post_dates(
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ChapterQuery {
-    book: String,
-    chapter: usize,
     index: usize,
 }
 
@@ -210,22 +774,26 @@ pub async fn get_q_chapter(
     match user {
         Some(user) => {
             let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
-            let reading_date = today_naive_date() + Duration::days(dates.offset);
-            let day_diff = (reading_date - dates.start_date).num_days() + 1;
 
             let readings = UserReadings::from_user(&state.db, user.id).await;
+            let progress =
+                UserProgress::from_user(&state.db, user.id, dates.reading_date()).await?;
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let t = |text| settings.locale.t(text);
 
-            if q.book.starts_with("Mark") {
-                return Ok(error_modal("Error", "wow").into_response());
-            }
+            let Some(reading) = readings.readings.get(q.index) else {
+                return Ok(error_modal(t("Error"), t("Unknown reading list")).into_response());
+            };
+            let portion = reading.portion(&dates);
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
 
             Ok(html! {
-                (fragment_readings_rows(&readings, day_diff, Some(q.index)))
+                (fragment_readings_rows(&readings, &progress, &dates, Some(q.index), settings.locale))
                 (
-                    match  fragment_chapter_content(q.book.parse::<Book>().unwrap(), q.chapter).await {
+                    match  fragment_chapter_content(&state.bibles, &settings, &portion, &annotations, None).await {
                         Ok(e) => e,
                         Err(_) => {
-                            return Ok(error_modal("Internal Server Error", "Error fetching chapter content").into_response());
+                            return Ok(error_modal(t("Internal Server Error"), t("Error fetching chapter content")).into_response());
                         }
 
                     }
@@ -237,6 +805,336 @@ pub async fn get_q_chapter(
     }
 }
 
+/// A chapter shown in the reading pane outside of the reading lists, e.g. from search results.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PassageQuery {
+    /// `Book::index`
+    book: usize,
+    chapter: i64,
+    /// Verse to highlight.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verse: Option<u32>,
+    /// Overrides the user's translation for this view.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+}
+
+impl PassageQuery {
+    /// The whole chapter, with `verse` as the start if given. `None` for unknown chapters.
+    fn passage(&self) -> Option<Passage> {
+        let book = Book::from_index(self.book)
+            .filter(|book| (1..=book.total_chapters() as i64).contains(&self.chapter))?;
+        Some(Passage {
+            verse: self.verse,
+            ..Passage::chapter(book, self.chapter)
+        })
+    }
+}
+
+pub async fn get_passage(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Query(q): Query<PassageQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let t = |text| settings.locale.t(text);
+            let Some(passage) = q.passage() else {
+                return Ok(error_modal(t("Error"), t("Unknown passage")).into_response());
+            };
+            let settings = UserSettings {
+                translation: q.translation.or(settings.translation),
+                ..settings
+            };
+            let focus = passage.verse.map(|verse| (passage.chapter, verse));
+            let portion = [passage];
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
+
+            Ok(
+                fragment_chapter_content(&state.bibles, &settings, &portion, &annotations, focus)
+                    .await?
+                    .into_response(),
+            )
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProgressRequest {
+    index: usize,
+    reading_idx: usize,
+    /// Position of the list as shown when the button was rendered, so a
+    /// request sent twice moves a catch-up list only once.
+    position: i64,
+}
+
+/// `hx-vals` of the progress button of a list.
+#[derive(Debug, Serialize)]
+struct ProgressVals {
+    index: usize,
+    position: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GotoQuery {
+    #[serde(rename = "ref")]
+    reference: String,
+}
+
+/// Show the passages of a typed reference such as "Yoh 3:16-18; Rm 8".
+pub async fn get_goto(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Query(q): Query<GotoQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let portion = match reference::parse_references(&q.reference) {
+                Ok(portion) => portion,
+                Err(e) => {
+                    return Ok(
+                        error_modal(settings.locale.t("Unknown passage"), &e.to_string())
+                            .into_response(),
+                    )
+                }
+            };
+            let annotations = Annotations::for_portion(&state.db, user.id, &portion).await?;
+
+            Ok(
+                fragment_chapter_content(&state.bibles, &settings, &portion, &annotations, None)
+                    .await?
+                    .into_response(),
+            )
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+/// Toggle the "read" mark of a single row in the readings sidebar.
+pub async fn post_progress(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<ProgressRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let t = |text| settings.locale.t(text);
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let mut readings = UserReadings::from_user(&state.db, user.id).await;
+            if form.index >= readings.readings.len() {
+                return Ok(error_modal(t("Error"), t("Unknown reading list")).into_response());
+            }
+
+            let mut progress =
+                UserProgress::from_user(&state.db, user.id, dates.reading_date()).await?;
+            let reading = &mut readings.readings[form.index];
+            let portion = reading.portion(&dates);
+            if reading.catch_up {
+                // Catch-up lists only move forward: reading the due portion advances the
+                // list. A repeated request finds the list moved on already.
+                if form.position == reading.position {
+                    progress
+                        .mark_portion(&state.db, form.index, &portion)
+                        .await?;
+                    reading.position += 1;
+                    readings.update(&state.db, form.index).await?;
+                }
+            } else {
+                progress
+                    .toggle_portion(&state.db, form.index, &portion)
+                    .await?;
+            }
+
+            Ok(fragment_readings_rows(
+                &readings,
+                &progress,
+                &dates,
+                Some(form.reading_idx),
+                settings.locale,
+            )
+            .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadingSettingsQuery {
+    reading_idx: usize,
+}
+
+/// Modal with the settings of a single reading list.
+pub async fn get_reading_settings(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Path(idx): Path<usize>,
+    Query(q): Query<ReadingSettingsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let t = |text| locale.t(text);
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let readings = UserReadings::from_user(&state.db, user.id).await;
+            let Some(reading) = readings.readings.get(idx) else {
+                return Ok(error_modal(t("Error"), t("Unknown reading list")).into_response());
+            };
+            let behind = reading.days_behind(&dates);
+            let start_date = reading.start_date.map(|d| d.format("%Y-%m-%d").to_string());
+
+            Ok(ui_modal(
+                &ModalCfg::new().auto_open().with_content(html! {
+                    form
+                        hx-post=(format!("/readings/{idx}"))
+                        hx-target="#readings"
+                        hx-swap="outerHTML"
+                        class="bg-white text-gray-900"
+                    {
+                        input type="hidden" name="reading_idx" value=(q.reading_idx);
+                        div class="px-4 pb-4 pt-5 sm:p-6 sm:pb-4" {
+                            h3 class="text-base font-semibold leading-6" id="modal-title" {
+                                (reading.label(locale))
+                            }
+                            p class="text-sm text-gray-500" {
+                                (t("Day {n}").replace("{n}", &reading.scheduled_day(&dates).to_string()))
+                                @if reading.paused_on.is_some() { (t(" (paused)")) }
+                            }
+                            div class="mt-4 flex flex-col gap-1 text-sm" {
+                                label for="list-start-date" class="font-semibold" { (t("Start date")) }
+                                input type="date" id="list-start-date" name="start_date" value=[start_date]
+                                    class="text-sm rounded-sm border-gray-300";
+                                p class="text-gray-500" { (t("Leave empty to follow the global start date.")) }
+                            }
+                            label class="mt-4 flex items-start gap-3 text-sm" {
+                                input type="checkbox" name="paused" class="mt-1" checked[reading.paused_on.is_some()];
+                                div {
+                                    div class="font-semibold" { (t("Paused")) }
+                                    p class="text-gray-500" {
+                                        (t("The list stays on its current chapter until resumed. Other lists are not affected."))
+                                    }
+                                }
+                            }
+                            label class="mt-4 flex items-start gap-3 text-sm" {
+                                input type="checkbox" name="catch_up" class="mt-1" checked[reading.catch_up];
+                                div {
+                                    div class="font-semibold" { (t("Catch-up mode")) }
+                                    p class="text-gray-500" {
+                                        (t("The list only advances when its chapter is marked as read, so missed days are not skipped."))
+                                    }
+                                }
+                            }
+                            @if behind > 0 {
+                                p class="mt-3 text-sm text-red-600" { (t("{n} days behind").replace("{n}", &behind.to_string())) }
+                            }
+                        }
+                        div class="bg-gray-50 px-4 py-3 sm:flex sm:flex-row-reverse sm:px-6 gap-2" {
+                            (ui_button(html! { (t("Save")) },
+                                &ButtonCfg::new()
+                                    .with_color(Color::Default)
+                                    .with_type(ButtonType::Submit)
+                                    .on_click("open = false"),
+                                &HxCfg::new()
+                            ))
+                            button type="submit" name="restart" value="true" x-on:click="open = false"
+                                class="text-sm font-medium rounded-xs px-4 py-2 h-9 text-red-600 hover:bg-red-50" {
+                                (t("Restart from the beginning"))
+                            }
+                            (ui_button(html! { (t("Cancel")) },
+                                &ButtonCfg::new()
+                                    .with_color(Color::Alternative)
+                                    .on_click("open = false"),
+                                &HxCfg::new()
+                            ))
+                        }
+                    }
+                }),
+            )
+            .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadingSettingsRequest {
+    catch_up: Option<String>,
+    paused: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_iso_date")]
+    start_date: Option<NaiveDate>,
+    restart: Option<String>,
+    reading_idx: usize,
+}
+
+fn deserialize_optional_iso_date<'de, D>(d: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(d)?;
+    if s.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| D::Error::custom("wrong date format"))
+}
+
+pub async fn post_reading_settings(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Path(idx): Path<usize>,
+    Form(form): Form<ReadingSettingsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let t = |text| settings.locale.t(text);
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let reading_date = dates.reading_date();
+            let mut readings = UserReadings::from_user(&state.db, user.id).await;
+            let progress = UserProgress::from_user(&state.db, user.id, reading_date).await?;
+            let Some(reading) = readings.readings.get_mut(idx) else {
+                return Ok(error_modal(t("Error"), t("Unknown reading list")).into_response());
+            };
+
+            if form.restart.is_some() {
+                reading.restart(reading_date);
+            } else {
+                reading.start_date = form.start_date;
+                match (form.paused.is_some(), reading.paused_on.is_some()) {
+                    (true, false) => reading.pause(reading_date),
+                    (false, true) => reading.resume(reading_date, &dates),
+                    _ => {}
+                }
+            }
+
+            let catch_up = form.catch_up.is_some();
+            if catch_up && !reading.catch_up {
+                // Start catching up from today's portion, or tomorrow's if it's already read.
+                let day = reading.scheduled_day(&dates);
+                let portion = reading.rule.portion(&reading.books, day);
+                let read_today = progress.is_portion_read(idx, &portion);
+                reading.position = day - 1 + i64::from(read_today);
+            }
+            reading.catch_up = catch_up;
+            readings.update(&state.db, idx).await?;
+
+            Ok(fragment_readings_rows(
+                &readings,
+                &progress,
+                &dates,
+                Some(form.reading_idx),
+                settings.locale,
+            )
+            .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
+
 pub fn redirect_index() -> Markup {
     html! {
         head {
@@ -288,22 +1186,52 @@ fn error_modal(title: &str, content: &str) -> (HeaderMap, Markup) {
 
 fn fragment_readings_rows(
     readings: &UserReadings,
-    day_diff: i64,
+    progress: &UserProgress,
+    dates: &UserDates,
     active_idx: Option<usize>,
+    locale: Locale,
 ) -> Markup {
     tracing::trace!("fragment_reading_rows");
 
+    let rows: Vec<_> = readings
+        .readings
+        .iter()
+        .enumerate()
+        .map(|(idx, reading)| {
+            let portion = reading.portion(dates);
+            let is_done = if reading.catch_up {
+                reading.position >= reading.scheduled_day(dates)
+            } else {
+                portion.is_empty() || progress.is_portion_read(idx, &portion)
+            };
+            (idx, reading, portion, is_done)
+        })
+        .collect();
+    let done = rows.iter().filter(|(_, _, _, is_done)| *is_done).count();
+
     html! {
         div id="readings" class="w-full flex flex-col gap-2" {
-            h2 class="font-bold text-md mb-3 mt-4" { "Readings" }
+            div class="flex justify-between items-baseline mb-3 mt-4" {
+                h2 class="font-bold text-md" { (locale.t("Readings")) }
+                span class="text-xs text-foreground/60" {
+                    (locale.t("{done}/{total} read").replace("{done}", &done.to_string()).replace("{total}", &rows.len().to_string()))
+                }
+            }
 
             input type="hidden" name="reading_idx" value=(active_idx.unwrap_or(0));
-            @for (idx, (info, _)) in readings.readings.iter().map(|s| get_day_plan(s, day_diff)).enumerate() {
+            @for (idx, reading, portion, is_done) in &rows {
+                @let idx = *idx;
                 @let is_active = matches!(active_idx, Some(i) if i == idx);
+                @let behind = reading.days_behind(dates);
+                @let (books, chapters) = portion_parts(portion, locale);
 
                 @let color = if is_active { Color::Default } else {Color::Alternative};
 
-                @let vals = serde_json::to_string(&ChapterQuery{book: info.book.clone().to_string(), chapter: info.chapter as usize, index: idx}).expect("serializable struct");
+                @let vals = serde_json::to_string(&ChapterQuery{index: idx}).expect("serializable struct");
+                @let progress_vals = serde_json::to_string(&ProgressVals {
+                    index: idx,
+                    position: reading.position,
+                }).expect("serializable struct");
                 @let hx_builder = {
                     let mut hx_builder = HxCfg::new();
                     if !is_active {
@@ -317,23 +1245,83 @@ fn fragment_readings_rows(
                     hx_builder
                 };
 
-                (ui_button(
-                    html! {
-                        div class="flex gap-4 w-full justify-between text-sm font-normal" {
-                            span { (&info.book) }
-                            span { (info.chapter) }
-                        }
-                    },
-                    &ButtonCfg::new()
-                        .with_color(color)
-                        .with_id(&format!("{}-{}", &info.book, info.chapter).replace(' ', "_")),
-                    &hx_builder
-                ))
+                div class="flex gap-2 items-center" {
+                    (ui_button(
+                        html! {
+                            div class="flex gap-4 w-full justify-between text-sm font-normal" {
+                                span {
+                                    (books)
+                                    @if reading.paused_on.is_some() {
+                                        span class="ml-2 text-xs text-foreground/50" { (locale.t("paused")) }
+                                    }
+                                    @if behind > 0 {
+                                        span class="ml-2 text-xs text-red-500" title=(locale.t("{n} days behind").replace("{n}", &behind.to_string())) {
+                                            "-" (behind)
+                                        }
+                                    }
+                                }
+                                span { (chapters) }
+                            }
+                        },
+                        &ButtonCfg::new()
+                            .with_color(color)
+                            .with_cn("flex-1")
+                            .with_id(&format!("reading-{idx}")),
+                        &hx_builder
+                    ))
+                    @if !portion.is_empty() {
+                        (fragment_progress_toggle(*is_done && !reading.catch_up, &progress_vals, locale))
+                    }
+                    (fragment_reading_settings_btn(idx, locale))
+                }
             }
         }
     }
 }
 
+fn fragment_reading_settings_btn(idx: usize, locale: Locale) -> Markup {
+    ui_button(
+        html! {
+            svg class="w-4 h-4" aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" {
+                path stroke="currentColor" stroke-linecap="round" stroke-width="2" d="M6 12h.01m6 0h.01m5.99 0h.01";
+            }
+            span class="sr-only" { (locale.t("List settings")) }
+        },
+        &ButtonCfg::new()
+            .with_color(Color::Alternative)
+            .with_cn("px-2"),
+        &HxCfg::new()
+            .with_get(&format!("/readings/{idx}"))
+            .with_include("[name='reading_idx']")
+            .with_target("#modal-container"),
+    )
+}
+
+fn fragment_progress_toggle(is_read: bool, vals: &str, locale: Locale) -> Markup {
+    let color = if is_read {
+        Color::Green
+    } else {
+        Color::Alternative
+    };
+
+    ui_button(
+        html! {
+            svg class=(if is_read { "w-4 h-4" } else { "w-4 h-4 opacity-30" })
+                aria-hidden="true" xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 16 12" {
+                path stroke="currentColor" stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M1 5.917 5.724 10.5 15 1.5";
+            }
+            span class="sr-only" { (locale.t(if is_read { "Mark as unread" } else { "Mark as read" })) }
+        },
+        &ButtonCfg::new().with_color(color).with_cn("px-2"),
+        &HxCfg::new()
+            .with_post("/progress")
+            .with_vals(vals)
+            .with_include("[name='reading_idx']")
+            .with_target("#readings")
+            .with_swap("outerHTML"),
+    )
+}
+
 fn fmt_naivedate(date: NaiveDate) -> String {
     format!("{}/{}/{}", date.day0() + 1, date.month0() + 1, date.year())
 }
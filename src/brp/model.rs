@@ -1,127 +1,217 @@
-use super::books::Book;
-use crate::utils::today_naive_date;
-use chrono::{Datelike, NaiveDate};
-use lazy_static::lazy_static;
-use sqlx::SqlitePool;
-
-lazy_static! {
-    static ref DEFULT_READINGS: Vec<Vec<Book>> = vec![
-        vec![Book::Matthew, Book::Mark, Book::Luke, Book::John],
-        vec![
-            Book::Genesis,
-            Book::Exodus,
-            Book::Leviticus,
-            Book::Numbers,
-            Book::Deuteronomy,
-        ],
-        vec![
-            Book::Romans,
-            Book::FirstCorinthians,
-            Book::SecondCorinthians,
-            Book::Galatians,
-            Book::Ephesians,
-            Book::Philippians,
-            Book::Colossians,
-            Book::Hebrews,
-        ],
-        vec![
-            Book::FirstThessalonians,
-            Book::SecondThessalonians,
-            Book::FirstTimothy,
-            Book::SecondTimothy,
-            Book::Titus,
-            Book::Philemon,
-            Book::James,
-            Book::FirstPeter,
-            Book::SecondPeter,
-            Book::FirstJohn,
-            Book::SecondJohn,
-            Book::ThirdJohn,
-            Book::Jude,
-            Book::Revelation,
-        ],
-        vec![Book::Job, Book::Ecclesiastes, Book::SongOfSolomon],
-        vec![Book::Psalms],
-        vec![Book::Proverbs],
-        vec![
-            Book::Joshua,
-            Book::Judges,
-            Book::Ruth,
-            Book::FirstSamuel,
-            Book::SecondSamuel,
-            Book::FirstKings,
-            Book::SecondKings,
-            Book::FirstChronicles,
-            Book::SecondChronicles,
-            Book::Ezra,
-            Book::Nehemiah,
-            Book::Esther,
-        ],
-        vec![
-            Book::Isaiah,
-            Book::Jeremiah,
-            Book::Lamentations,
-            Book::Ezekiel,
-            Book::Daniel,
-            Book::Hosea,
-            Book::Joel,
-            Book::Amos,
-            Book::Obadiah,
-            Book::Jonah,
-            Book::Micah,
-            Book::Nahum,
-            Book::Habakkuk,
-            Book::Zephaniah,
-            Book::Haggai,
-            Book::Zechariah,
-            Book::Malachi,
-        ],
-        vec![Book::Acts],
-    ];
+use super::{
+    books::{Book, Passage},
+    plan::{Plan, Rule},
+};
+use crate::{i18n::Locale, utils::today_naive_date};
+use chrono::{Datelike, Duration, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// A single reading list with its scheduling state.
+#[derive(Debug, Clone)]
+pub struct Reading {
+    pub books: Vec<Book>,
+    pub rule: Rule,
+    /// In catch-up mode the list only advances when its portion is marked read,
+    /// instead of following the calendar.
+    pub catch_up: bool,
+    /// Number of portions read while in catch-up mode.
+    pub position: i64,
+    /// Overrides `UserDates.start_date` for this list only.
+    pub start_date: Option<NaiveDate>,
+    /// While set, the list's schedule stays on the day it was paused.
+    pub paused_on: Option<NaiveDate>,
+}
+
+impl Reading {
+    pub fn new(books: Vec<Book>) -> Self {
+        Self {
+            books,
+            rule: Rule::Cycle,
+            catch_up: false,
+            position: 0,
+            start_date: None,
+            paused_on: None,
+        }
+    }
+
+    /// Short description of the list, e.g. "Matthew – John".
+    pub fn label(&self, locale: Locale) -> String {
+        match (self.books.first(), self.books.last()) {
+            (Some(first), Some(last)) if first != last => {
+                format!("{} – {}", first.name(locale), last.name(locale))
+            }
+            (Some(first), _) => first.name(locale).to_string(),
+            _ => String::new(),
+        }
+    }
+
+    pub fn start_date(&self, dates: &UserDates) -> NaiveDate {
+        self.start_date.unwrap_or(dates.start_date)
+    }
+
+    /// 1-based day number of the calendar schedule of this list.
+    pub fn scheduled_day(&self, dates: &UserDates) -> i64 {
+        let reading_date = match self.paused_on {
+            Some(paused_on) => paused_on.min(dates.reading_date()),
+            None => dates.reading_date(),
+        };
+        (reading_date - self.start_date(dates)).num_days() + 1
+    }
+
+    /// Day number (as in `get_day_plan`) of the portion currently due in this list.
+    pub fn current_day(&self, dates: &UserDates) -> i64 {
+        if self.catch_up {
+            self.position + 1
+        } else {
+            self.scheduled_day(dates)
+        }
+    }
+
+    /// Passages currently due in this list.
+    pub fn portion(&self, dates: &UserDates) -> Vec<Passage> {
+        self.rule.portion(&self.books, self.current_day(dates))
+    }
+
+    /// Passages due `days` after (or before, when negative) the reading date,
+    /// assuming a catch-up list is kept up with from now on. Nothing is due
+    /// before the list starts.
+    pub fn upcoming_portion(&self, dates: &UserDates, days: i64) -> Vec<Passage> {
+        let day = match (self.catch_up, self.paused_on) {
+            (true, Some(_)) => self.position + 1,
+            (true, None) => self.position + 1 + days,
+            (false, _) => self.scheduled_day(&UserDates {
+                start_date: dates.start_date,
+                offset: dates.offset + days,
+            }),
+        };
+        if day < 1 {
+            return Vec::new();
+        }
+        self.rule.portion(&self.books, day)
+    }
+
+    /// Number of scheduled days before today whose portion hasn't been read yet.
+    pub fn days_behind(&self, dates: &UserDates) -> i64 {
+        if self.catch_up {
+            (self.scheduled_day(dates) - self.position - 1).max(0)
+        } else {
+            0
+        }
+    }
+
+    /// Restart the list from its first chapter on `date`.
+    pub fn restart(&mut self, date: NaiveDate) {
+        self.start_date = Some(date);
+        self.paused_on = None;
+        self.position = 0;
+    }
+
+    pub fn pause(&mut self, date: NaiveDate) {
+        if self.paused_on.is_none() {
+            self.paused_on = Some(date);
+        }
+    }
+
+    /// Resume a paused list, shifting its start date by the length of the pause.
+    pub fn resume(&mut self, date: NaiveDate, dates: &UserDates) {
+        if let Some(paused_on) = self.paused_on.take() {
+            if date > paused_on {
+                self.start_date = Some(self.start_date(dates) + (date - paused_on));
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct UserReadings {
     user_id: i64,
-    pub readings: Vec<Vec<Book>>,
+    pub readings: Vec<Reading>,
 }
 
 impl UserReadings {
     pub fn new(user_id: i64, readings: Vec<Vec<Book>>) -> Self {
+        Self {
+            user_id,
+            readings: readings.into_iter().map(Reading::new).collect(),
+        }
+    }
+
+    pub fn with_readings(user_id: i64, readings: Vec<Reading>) -> Self {
         Self { user_id, readings }
     }
 
     pub fn new_with_default_readings(user_id: i64) -> Self {
-        Self {
-            user_id,
-            readings: DEFULT_READINGS.clone(),
-        }
+        Self::with_readings(user_id, Plan::Horner.readings())
     }
 
     /// Delete all readings for `self.id` from table `readings`
     /// and replace it with new `self.readings`.
-    pub async fn replace_current(&self, pool: &SqlitePool) {
+    pub async fn replace_current(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        self.write_readings(&mut tx).await?;
+        tx.commit().await
+    }
+
+    /// Like `replace_current`, moving the progress of the lists along in the
+    /// same transaction, see `UserProgress::remap_lists`.
+    pub async fn replace_current_remapping(
+        &self,
+        pool: &SqlitePool,
+        mapping: &[Option<usize>],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        self.write_readings(&mut tx).await?;
+        UserProgress::remap_lists(&mut tx, self.user_id, mapping).await?;
+        tx.commit().await
+    }
+
+    async fn write_readings(&self, conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
         let res = sqlx::query!("DELETE FROM readings WHERE user_id = ?", self.user_id)
-            .execute(pool)
-            .await
-            .unwrap();
-        println!("Affected rows: {}", res.rows_affected());
+            .execute(&mut *conn)
+            .await?;
+        tracing::debug!("deleted {} readings", res.rows_affected());
         for (i, reading) in self.readings.iter().enumerate() {
-            let reading: Vec<_> = reading.into_iter().map(|s| s.to_string()).collect();
-            let text = reading.join("|");
+            let books: Vec<_> = reading.books.iter().map(|s| s.to_string()).collect();
+            let text = books.join("|");
 
             let idx = i as i64;
+            let rule = reading.rule.to_string();
             let res = sqlx::query!(
-                "INSERT INTO readings (user_id, reading, idx) VALUES (?1, ?2, ?3)",
+                "INSERT INTO readings (user_id, reading, idx, rule, catch_up, position, start_date, paused_on)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 self.user_id,
                 text,
-                idx
+                idx,
+                rule,
+                reading.catch_up,
+                reading.position,
+                reading.start_date,
+                reading.paused_on
             )
-            .execute(pool)
-            .await
-            .unwrap();
-            println!("Inserted reading: {}", res.rows_affected());
+            .execute(&mut *conn)
+            .await?;
+            tracing::debug!("inserted {} reading", res.rows_affected());
         }
+        Ok(())
+    }
+
+    /// Persist the scheduling state of the reading list at `idx`.
+    pub async fn update(&self, pool: &SqlitePool, idx: usize) -> Result<(), sqlx::Error> {
+        let reading = &self.readings[idx];
+        let idx = idx as i64;
+        sqlx::query!(
+            "UPDATE readings SET catch_up = ?1, position = ?2, start_date = ?3, paused_on = ?4
+            WHERE user_id = ?5 AND idx = ?6",
+            reading.catch_up,
+            reading.position,
+            reading.start_date,
+            reading.paused_on,
+            self.user_id,
+            idx
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
     }
 
     pub async fn from_user(pool: &SqlitePool, user_id: i64) -> Self {
@@ -135,12 +225,19 @@ impl UserReadings {
 
         let mut readings = Vec::with_capacity(records.capacity());
         for rec in records {
-            let reading: Vec<_> = rec
+            let books: Vec<_> = rec
                 .reading
                 .split("|")
                 .map(|s| s.parse::<Book>().unwrap())
                 .collect();
-            readings.push(reading);
+            readings.push(Reading {
+                books,
+                rule: rec.rule.parse::<Rule>().unwrap_or(Rule::Cycle),
+                catch_up: rec.catch_up,
+                position: rec.position,
+                start_date: rec.start_date,
+                paused_on: rec.paused_on,
+            });
         }
         Self { user_id, readings }
     }
@@ -176,6 +273,11 @@ impl UserDates {
         }
     }
 
+    /// The date currently being read, i.e. today shifted by `offset`.
+    pub fn reading_date(&self) -> NaiveDate {
+        today_naive_date() + Duration::days(self.offset)
+    }
+
     pub async fn from_user_or_set_default(pool: &SqlitePool, user_id: i64) -> Self {
         let res = sqlx::query!(
             "SELECT start_date, offset FROM dates WHERE user_id = ?",
@@ -196,3 +298,265 @@ impl UserDates {
         }
     }
 }
+
+/// Per-user preferences, one optional column each.
+#[derive(Debug, Default)]
+pub struct UserSettings {
+    /// Code of the preferred translation, see `BibleRegistry`.
+    pub translation: Option<String>,
+    /// Translation shown side by side with `translation`.
+    pub parallel: Option<String>,
+    pub locale: Locale,
+}
+
+impl UserSettings {
+    pub async fn from_user(pool: &SqlitePool, user_id: i64) -> Result<Self, sqlx::Error> {
+        let rec = sqlx::query!(
+            "SELECT translation, parallel, locale FROM user_settings WHERE user_id = ?",
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(match rec {
+            Some(rec) => Self {
+                translation: rec.translation,
+                parallel: rec.parallel,
+                locale: rec
+                    .locale
+                    .and_then(|locale| locale.parse().ok())
+                    .unwrap_or_default(),
+            },
+            None => Self::default(),
+        })
+    }
+
+    pub async fn set_translations(
+        pool: &SqlitePool,
+        user_id: i64,
+        translation: &str,
+        parallel: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO user_settings (user_id, translation, parallel) VALUES (?1, ?2, ?3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET translation = excluded.translation, parallel = excluded.parallel",
+            user_id,
+            translation,
+            parallel
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_locale(
+        pool: &SqlitePool,
+        user_id: i64,
+        locale: Locale,
+    ) -> Result<(), sqlx::Error> {
+        let locale = locale.code();
+        sqlx::query!(
+            "INSERT INTO user_settings (user_id, locale) VALUES (?1, ?2)
+            ON CONFLICT (user_id) DO UPDATE SET locale = excluded.locale",
+            user_id,
+            locale
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEntry {
+    pub reading_idx: usize,
+    pub book: Book,
+    pub chapter: i64,
+}
+
+/// Chapters marked as read by a user on a single reading date.
+#[derive(Debug)]
+pub struct UserProgress {
+    user_id: i64,
+    pub read_on: NaiveDate,
+    pub entries: Vec<ProgressEntry>,
+}
+
+impl UserProgress {
+    pub async fn from_user(
+        pool: &SqlitePool,
+        user_id: i64,
+        read_on: NaiveDate,
+    ) -> Result<Self, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT reading_idx, book, chapter FROM progress WHERE user_id = ?1 AND read_on = ?2",
+            user_id,
+            read_on
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let entries = records
+            .into_iter()
+            .filter_map(|rec| {
+                Some(ProgressEntry {
+                    reading_idx: rec.reading_idx as usize,
+                    book: rec.book.parse::<Book>().ok()?,
+                    chapter: rec.chapter,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            user_id,
+            read_on,
+            entries,
+        })
+    }
+
+    /// Move the progress of reordered reading lists along with them.
+    /// `mapping[new_idx]` is the index the list had before; progress of lists
+    /// that no longer exist is kept (for statistics) under index -1.
+    async fn remap_lists(
+        conn: &mut SqliteConnection,
+        user_id: i64,
+        mapping: &[Option<usize>],
+    ) -> Result<(), sqlx::Error> {
+        // Park the moved rows on negative indices first so that swaps don't collide.
+        for (new_idx, old_idx) in mapping.iter().enumerate() {
+            let Some(old_idx) = old_idx else {
+                continue;
+            };
+            let parked = -2 - new_idx as i64;
+            let old_idx = *old_idx as i64;
+            sqlx::query!(
+                "UPDATE progress SET reading_idx = ?1 WHERE user_id = ?2 AND reading_idx = ?3",
+                parked,
+                user_id,
+                old_idx
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        sqlx::query!(
+            "UPDATE OR REPLACE progress SET reading_idx = -1 WHERE user_id = ? AND reading_idx >= 0",
+            user_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query!(
+            "UPDATE progress SET reading_idx = -2 - reading_idx WHERE user_id = ? AND reading_idx <= -2",
+            user_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    pub fn is_read(&self, reading_idx: usize, book: &Book, chapter: i64) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.reading_idx == reading_idx && &e.book == book && e.chapter == chapter)
+    }
+
+    /// Whether every chapter of a (non-empty) portion is read. Chapters only partly
+    /// covered by a passage, like a section of Psalm 119, count as a whole.
+    pub fn is_portion_read(&self, reading_idx: usize, portion: &[Passage]) -> bool {
+        !portion.is_empty()
+            && portion_entries(reading_idx, portion)
+                .all(|e| self.is_read(reading_idx, &e.book, e.chapter))
+    }
+
+    /// Mark all chapters of the portion as read on `self.read_on`, or unmark them
+    /// if they already are. Returns whether the portion is read afterwards.
+    pub async fn toggle_portion(
+        &mut self,
+        pool: &SqlitePool,
+        reading_idx: usize,
+        portion: &[Passage],
+    ) -> Result<bool, sqlx::Error> {
+        let is_read = self.is_portion_read(reading_idx, portion);
+        for entry in portion_entries(reading_idx, portion) {
+            if is_read {
+                self.unmark(pool, entry).await?;
+            } else {
+                self.mark_read(pool, entry).await?;
+            }
+        }
+        Ok(!is_read)
+    }
+
+    pub async fn unmark(
+        &mut self,
+        pool: &SqlitePool,
+        entry: ProgressEntry,
+    ) -> Result<(), sqlx::Error> {
+        let idx = entry.reading_idx as i64;
+        let book = entry.book.to_string();
+        sqlx::query!(
+            "DELETE FROM progress
+            WHERE user_id = ?1 AND reading_idx = ?2 AND book = ?3 AND chapter = ?4 AND read_on = ?5",
+            self.user_id,
+            idx,
+            book,
+            entry.chapter,
+            self.read_on
+        )
+        .execute(pool)
+        .await?;
+        self.entries.retain(|e| e != &entry);
+        Ok(())
+    }
+
+    pub async fn mark_portion(
+        &mut self,
+        pool: &SqlitePool,
+        reading_idx: usize,
+        portion: &[Passage],
+    ) -> Result<(), sqlx::Error> {
+        for entry in portion_entries(reading_idx, portion) {
+            self.mark_read(pool, entry).await?;
+        }
+        Ok(())
+    }
+
+    /// Mark the chapter as read on `self.read_on`. Marking it twice is a no-op.
+    pub async fn mark_read(
+        &mut self,
+        pool: &SqlitePool,
+        entry: ProgressEntry,
+    ) -> Result<(), sqlx::Error> {
+        let idx = entry.reading_idx as i64;
+        let book = entry.book.to_string();
+        sqlx::query!(
+            "INSERT OR IGNORE INTO progress (user_id, reading_idx, book, chapter, read_on)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            self.user_id,
+            idx,
+            book,
+            entry.chapter,
+            self.read_on
+        )
+        .execute(pool)
+        .await?;
+        if !self.entries.contains(&entry) {
+            self.entries.push(entry);
+        }
+        Ok(())
+    }
+}
+
+/// One progress entry per chapter touched by the portion.
+fn portion_entries(
+    reading_idx: usize,
+    portion: &[Passage],
+) -> impl Iterator<Item = ProgressEntry> + '_ {
+    portion.iter().flat_map(move |passage| {
+        passage.chapters().map(move |chapter| ProgressEntry {
+            reading_idx,
+            book: passage.book.clone(),
+            chapter,
+        })
+    })
+}
@@ -0,0 +1,81 @@
+use super::{model::UserReadings, plan::Plan};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        hx::{HxCfg, HxHeaderBuilder, HxSwap},
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg, ButtonType},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Form};
+use maud::{html, Markup};
+use serde::Deserialize;
+
+/// Plan picker shown instead of the readings while the user has no list yet.
+pub fn page_onboarding(locale: Locale) -> Markup {
+    html! {
+        div class="flex justify-center" {
+            form class="flex flex-col gap-6 w-[600px] py-12 px-4" hx-post="/onboarding" {
+                div {
+                    h1 class="text-xl font-bold" { (locale.t("Choose a reading plan")) }
+                    p class="text-sm text-foreground/60 mt-1" {
+                        (locale.t("The lists can be customised or replaced by another plan later in the settings."))
+                    }
+                }
+                div class="flex flex-col gap-3" {
+                    @for (i, plan) in Plan::all().into_iter().enumerate() {
+                        label class="flex items-start gap-3 border border-border rounded-sm p-4 bg-background-100 cursor-pointer hover:bg-foreground/5" {
+                            input type="radio" name="plan" value=(plan.slug()) class="mt-1" checked[i == 0];
+                            div {
+                                div class="font-semibold" { (locale.t(plan.name())) }
+                                p class="text-sm text-foreground/60" { (locale.t(plan.description())) }
+                            }
+                        }
+                    }
+                }
+                (ui_button(html! { (locale.t("Start reading")) },
+                    &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit),
+                    &HxCfg::new()
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnboardingRequest {
+    plan: String,
+}
+
+pub async fn post_onboarding(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<OnboardingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            // Only for new users, a repeated request must not replace their lists
+            // (that's what resetting on the settings page is for).
+            let current = UserReadings::from_user(&state.db, user.id).await;
+            if current.readings.is_empty() {
+                let plan = form.plan.parse::<Plan>().unwrap_or(Plan::Horner);
+                UserReadings::with_readings(user.id, plan.readings())
+                    .replace_current(&state.db)
+                    .await?;
+            }
+
+            Ok(HxHeaderBuilder::new()
+                .with_swap(HxSwap::None)
+                .with_redirect("/")
+                .build()
+                .into_response())
+        }
+        None => Ok(redirect_login().into_response()),
+    }
+}
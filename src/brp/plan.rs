@@ -0,0 +1,332 @@
+use super::{
+    books::{get_day_plan, reading_units, Book, Passage},
+    model::Reading,
+};
+use lazy_static::lazy_static;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+lazy_static! {
+    static ref HORNER: Vec<Vec<Book>> = vec![
+        vec![Book::Matthew, Book::Mark, Book::Luke, Book::John],
+        vec![
+            Book::Genesis,
+            Book::Exodus,
+            Book::Leviticus,
+            Book::Numbers,
+            Book::Deuteronomy,
+        ],
+        vec![
+            Book::Romans,
+            Book::FirstCorinthians,
+            Book::SecondCorinthians,
+            Book::Galatians,
+            Book::Ephesians,
+            Book::Philippians,
+            Book::Colossians,
+            Book::Hebrews,
+        ],
+        vec![
+            Book::FirstThessalonians,
+            Book::SecondThessalonians,
+            Book::FirstTimothy,
+            Book::SecondTimothy,
+            Book::Titus,
+            Book::Philemon,
+            Book::James,
+            Book::FirstPeter,
+            Book::SecondPeter,
+            Book::FirstJohn,
+            Book::SecondJohn,
+            Book::ThirdJohn,
+            Book::Jude,
+            Book::Revelation,
+        ],
+        vec![Book::Job, Book::Ecclesiastes, Book::SongOfSolomon],
+        vec![Book::Psalms],
+        vec![Book::Proverbs],
+        vec![
+            Book::Joshua,
+            Book::Judges,
+            Book::Ruth,
+            Book::FirstSamuel,
+            Book::SecondSamuel,
+            Book::FirstKings,
+            Book::SecondKings,
+            Book::FirstChronicles,
+            Book::SecondChronicles,
+            Book::Ezra,
+            Book::Nehemiah,
+            Book::Esther,
+        ],
+        vec![
+            Book::Isaiah,
+            Book::Jeremiah,
+            Book::Lamentations,
+            Book::Ezekiel,
+            Book::Daniel,
+            Book::Hosea,
+            Book::Joel,
+            Book::Amos,
+            Book::Obadiah,
+            Book::Jonah,
+            Book::Micah,
+            Book::Nahum,
+            Book::Habakkuk,
+            Book::Zephaniah,
+            Book::Haggai,
+            Book::Zechariah,
+            Book::Malachi,
+        ],
+        vec![Book::Acts],
+    ];
+    static ref MCCHEYNE: Vec<Vec<Book>> = vec![
+        Book::all()
+            .into_iter()
+            .take_while(|b| *b != Book::Job)
+            .collect(),
+        Book::all()
+            .into_iter()
+            .skip_while(|b| *b != Book::Matthew)
+            .collect(),
+        Book::all()
+            .into_iter()
+            .skip_while(|b| *b != Book::Job)
+            .take_while(|b| *b != Book::Matthew)
+            .filter(|b| *b != Book::Psalms)
+            .collect(),
+        vec![Book::Psalms],
+    ];
+    static ref CHRONOLOGICAL: Vec<Book> = vec![
+        Book::Genesis,
+        Book::Job,
+        Book::Exodus,
+        Book::Leviticus,
+        Book::Numbers,
+        Book::Deuteronomy,
+        Book::Joshua,
+        Book::Judges,
+        Book::Ruth,
+        Book::FirstSamuel,
+        Book::SecondSamuel,
+        Book::FirstChronicles,
+        Book::Psalms,
+        Book::FirstKings,
+        Book::Proverbs,
+        Book::Ecclesiastes,
+        Book::SongOfSolomon,
+        Book::SecondKings,
+        Book::SecondChronicles,
+        Book::Joel,
+        Book::Jonah,
+        Book::Amos,
+        Book::Hosea,
+        Book::Isaiah,
+        Book::Micah,
+        Book::Nahum,
+        Book::Zephaniah,
+        Book::Habakkuk,
+        Book::Jeremiah,
+        Book::Lamentations,
+        Book::Obadiah,
+        Book::Ezekiel,
+        Book::Daniel,
+        Book::Ezra,
+        Book::Haggai,
+        Book::Zechariah,
+        Book::Esther,
+        Book::Nehemiah,
+        Book::Malachi,
+        Book::Matthew,
+        Book::Mark,
+        Book::Luke,
+        Book::John,
+        Book::Acts,
+        Book::James,
+        Book::Galatians,
+        Book::FirstThessalonians,
+        Book::SecondThessalonians,
+        Book::FirstCorinthians,
+        Book::SecondCorinthians,
+        Book::Romans,
+        Book::Ephesians,
+        Book::Philippians,
+        Book::Colossians,
+        Book::Philemon,
+        Book::FirstTimothy,
+        Book::Titus,
+        Book::FirstPeter,
+        Book::Hebrews,
+        Book::SecondTimothy,
+        Book::SecondPeter,
+        Book::Jude,
+        Book::FirstJohn,
+        Book::SecondJohn,
+        Book::ThirdJohn,
+        Book::Revelation,
+    ];
+}
+
+/// How a reading list is turned into a daily portion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// One chapter a day, starting over once the list is finished.
+    Cycle,
+    /// Like `Cycle`, but long chapters such as Psalm 119 are read in sections over several days.
+    Sections,
+    /// The whole list spread evenly over the given number of days, then starting over.
+    /// Lists shorter than that get the occasional rest day.
+    Spread(i64),
+}
+
+impl Rule {
+    /// Passages to read on the 1-based `day` of the list, consecutive chapters joined
+    /// into one passage per book.
+    pub fn portion(&self, books: &[Book], day: i64) -> Vec<Passage> {
+        match *self {
+            Rule::Cycle => vec![get_day_plan(books, day).0],
+            Rule::Sections => {
+                let units = reading_units(books, true);
+                let idx = (day - 1).rem_euclid(units.len() as i64) as usize;
+                vec![units[idx].clone()]
+            }
+            Rule::Spread(days) => {
+                let units = reading_units(books, false);
+                let total = units.len() as i64;
+                let day = (day - 1).rem_euclid(days);
+                let from = (day * total / days) as usize;
+                let to = ((day + 1) * total / days) as usize;
+
+                let mut portion: Vec<Passage> = Vec::new();
+                for unit in &units[from..to] {
+                    let joined = portion.last_mut().is_some_and(|last| last.join(unit));
+                    if !joined {
+                        portion.push(unit.clone());
+                    }
+                }
+                portion
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Cycle => write!(f, "cycle"),
+            Rule::Sections => write!(f, "sections"),
+            Rule::Spread(days) => write!(f, "spread:{days}"),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "cycle" => Ok(Rule::Cycle),
+            None if s == "sections" => Ok(Rule::Sections),
+            Some(("spread", days)) => match days.parse::<i64>() {
+                Ok(days) if days > 0 => Ok(Rule::Spread(days)),
+                _ => Err("can't parse spread days"),
+            },
+            _ => Err("can't parse str to rule"),
+        }
+    }
+}
+
+/// Built-in reading plan templates a user can start from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    Horner,
+    McCheyne,
+    Chronological,
+    NewTestament,
+    PsalmsProverbs,
+}
+
+impl Plan {
+    pub fn all() -> [Plan; 5] {
+        [
+            Plan::Horner,
+            Plan::McCheyne,
+            Plan::Chronological,
+            Plan::NewTestament,
+            Plan::PsalmsProverbs,
+        ]
+    }
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Plan::Horner => "horner",
+            Plan::McCheyne => "mccheyne",
+            Plan::Chronological => "chronological",
+            Plan::NewTestament => "nt-in-a-year",
+            Plan::PsalmsProverbs => "psalms-proverbs",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Plan::Horner => "Grant Horner",
+            Plan::McCheyne => "M'Cheyne",
+            Plan::Chronological => "Chronological",
+            Plan::NewTestament => "New Testament in a year",
+            Plan::PsalmsProverbs => "Psalms & Proverbs monthly",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Plan::Horner => "Ten lists, one chapter from each every day. Each list starts over when finished.",
+            Plan::McCheyne => "Four lists (Old Testament history, New Testament, wisdom and prophets, Psalms), one chapter from each every day and Psalm 119 in sections.",
+            Plan::Chronological => "The whole Bible in the order the events happened, in a year.",
+            Plan::NewTestament => "Matthew to Revelation in a year, with the occasional rest day.",
+            Plan::PsalmsProverbs => "All the Psalms every 30 days and Proverbs every 31 days.",
+        }
+    }
+
+    pub fn readings(&self) -> Vec<Reading> {
+        let lists: Vec<(Vec<Book>, Rule)> = match self {
+            Plan::Horner => HORNER.iter().map(|l| (l.clone(), Rule::Cycle)).collect(),
+            Plan::McCheyne => MCCHEYNE
+                .iter()
+                .map(|l| (l.clone(), Rule::Sections))
+                .collect(),
+            Plan::Chronological => vec![(CHRONOLOGICAL.clone(), Rule::Spread(365))],
+            Plan::NewTestament => vec![(
+                Book::all()
+                    .into_iter()
+                    .skip_while(|b| *b != Book::Matthew)
+                    .collect(),
+                Rule::Spread(365),
+            )],
+            Plan::PsalmsProverbs => vec![
+                (vec![Book::Psalms], Rule::Spread(30)),
+                (vec![Book::Proverbs], Rule::Spread(31)),
+            ],
+        };
+
+        lists
+            .into_iter()
+            .map(|(books, rule)| Reading {
+                rule,
+                ..Reading::new(books)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Plan {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Plan::all()
+            .into_iter()
+            .find(|p| p.slug() == s)
+            .ok_or("can't parse str to plan")
+    }
+}
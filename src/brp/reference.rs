@@ -0,0 +1,476 @@
+use super::books::{Book, Passage};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Names and common abbreviations of each book, English and Indonesian (LAI).
+/// Matching ignores case, spaces, dots and hyphens, so "1 Kor", "1kor" and "1 Kor."
+/// are the same.
+const BOOK_NAMES: [(Book, &[&str]); 66] = [
+    (Book::Genesis, &["Genesis", "Gen", "Gn", "Kejadian", "Kej"]),
+    (Book::Exodus, &["Exodus", "Exod", "Ex", "Keluaran", "Kel"]),
+    (Book::Leviticus, &["Leviticus", "Lev", "Lv", "Imamat", "Im"]),
+    (Book::Numbers, &["Numbers", "Num", "Nm", "Bilangan", "Bil"]),
+    (
+        Book::Deuteronomy,
+        &["Deuteronomy", "Deut", "Dt", "Ulangan", "Ul"],
+    ),
+    (Book::Joshua, &["Joshua", "Josh", "Jos", "Yosua", "Yos"]),
+    (
+        Book::Judges,
+        &["Judges", "Judg", "Jdg", "Hakim-hakim", "Hak"],
+    ),
+    (Book::Ruth, &["Ruth", "Ru", "Rut"]),
+    (Book::FirstSamuel, &["1 Samuel", "1 Sam", "1 Sa", "1 Sm"]),
+    (Book::SecondSamuel, &["2 Samuel", "2 Sam", "2 Sa", "2 Sm"]),
+    (
+        Book::FirstKings,
+        &["1 Kings", "1 Kgs", "1 Ki", "1 Raja-raja", "1 Raj"],
+    ),
+    (
+        Book::SecondKings,
+        &["2 Kings", "2 Kgs", "2 Ki", "2 Raja-raja", "2 Raj"],
+    ),
+    (
+        Book::FirstChronicles,
+        &["1 Chronicles", "1 Chr", "1 Ch", "1 Tawarikh", "1 Taw"],
+    ),
+    (
+        Book::SecondChronicles,
+        &["2 Chronicles", "2 Chr", "2 Ch", "2 Tawarikh", "2 Taw"],
+    ),
+    (Book::Ezra, &["Ezra", "Ezr"]),
+    (Book::Nehemiah, &["Nehemiah", "Neh", "Nehemia"]),
+    (Book::Esther, &["Esther", "Esth", "Est", "Ester"]),
+    (Book::Job, &["Job", "Jb", "Ayub", "Ayb"]),
+    (
+        Book::Psalms,
+        &["Psalms", "Psalm", "Ps", "Psa", "Mazmur", "Mzm"],
+    ),
+    (Book::Proverbs, &["Proverbs", "Prov", "Pr", "Amsal", "Ams"]),
+    (
+        Book::Ecclesiastes,
+        &["Ecclesiastes", "Eccl", "Ecc", "Pengkhotbah", "Pkh"],
+    ),
+    (
+        Book::SongOfSolomon,
+        &[
+            "Song of Solomon",
+            "Song of Songs",
+            "Song",
+            "SoS",
+            "Kidung Agung",
+            "Kid",
+        ],
+    ),
+    (Book::Isaiah, &["Isaiah", "Isa", "Is", "Yesaya", "Yes"]),
+    (Book::Jeremiah, &["Jeremiah", "Jer", "Yeremia", "Yer"]),
+    (
+        Book::Lamentations,
+        &["Lamentations", "Lam", "Ratapan", "Rat"],
+    ),
+    (
+        Book::Ezekiel,
+        &["Ezekiel", "Ezek", "Eze", "Yehezkiel", "Yeh"],
+    ),
+    (Book::Daniel, &["Daniel", "Dan", "Dn"]),
+    (Book::Hosea, &["Hosea", "Hos"]),
+    (Book::Joel, &["Joel", "Jl", "Yoel", "Yl"]),
+    (Book::Amos, &["Amos", "Am"]),
+    (Book::Obadiah, &["Obadiah", "Obad", "Ob", "Obaja"]),
+    (Book::Jonah, &["Jonah", "Jon", "Yunus", "Yun"]),
+    (Book::Micah, &["Micah", "Mic", "Mikha", "Mi"]),
+    (Book::Nahum, &["Nahum", "Nah"]),
+    (Book::Habakkuk, &["Habakkuk", "Hab", "Habakuk"]),
+    (
+        Book::Zephaniah,
+        &["Zephaniah", "Zeph", "Zep", "Zefanya", "Zef"],
+    ),
+    (Book::Haggai, &["Haggai", "Hag", "Hagai"]),
+    (
+        Book::Zechariah,
+        &["Zechariah", "Zech", "Zec", "Zakharia", "Za"],
+    ),
+    (Book::Malachi, &["Malachi", "Mal", "Maleakhi"]),
+    (Book::Matthew, &["Matthew", "Matt", "Mt", "Matius", "Mat"]),
+    (Book::Mark, &["Mark", "Mk", "Mrk", "Markus"]),
+    (Book::Luke, &["Luke", "Lk", "Luk", "Lukas"]),
+    (Book::John, &["John", "Jn", "Jhn", "Yohanes", "Yoh"]),
+    (
+        Book::Acts,
+        &["Acts", "Ac", "Kisah Para Rasul", "Kisah Rasul", "Kis"],
+    ),
+    (Book::Romans, &["Romans", "Rom", "Rm", "Roma"]),
+    (
+        Book::FirstCorinthians,
+        &["1 Corinthians", "1 Cor", "1 Co", "1 Korintus", "1 Kor"],
+    ),
+    (
+        Book::SecondCorinthians,
+        &["2 Corinthians", "2 Cor", "2 Co", "2 Korintus", "2 Kor"],
+    ),
+    (Book::Galatians, &["Galatians", "Gal", "Galatia"]),
+    (Book::Ephesians, &["Ephesians", "Eph", "Efesus", "Ef"]),
+    (
+        Book::Philippians,
+        &["Philippians", "Phil", "Php", "Filipi", "Flp"],
+    ),
+    (Book::Colossians, &["Colossians", "Col", "Kolose", "Kol"]),
+    (
+        Book::FirstThessalonians,
+        &[
+            "1 Thessalonians",
+            "1 Thess",
+            "1 Th",
+            "1 Tesalonika",
+            "1 Tes",
+        ],
+    ),
+    (
+        Book::SecondThessalonians,
+        &[
+            "2 Thessalonians",
+            "2 Thess",
+            "2 Th",
+            "2 Tesalonika",
+            "2 Tes",
+        ],
+    ),
+    (
+        Book::FirstTimothy,
+        &["1 Timothy", "1 Tim", "1 Ti", "1 Timotius"],
+    ),
+    (
+        Book::SecondTimothy,
+        &["2 Timothy", "2 Tim", "2 Ti", "2 Timotius"],
+    ),
+    (Book::Titus, &["Titus", "Tit"]),
+    (
+        Book::Philemon,
+        &["Philemon", "Phlm", "Philem", "Filemon", "Flm"],
+    ),
+    (Book::Hebrews, &["Hebrews", "Heb", "Ibrani", "Ibr"]),
+    (Book::James, &["James", "Jas", "Jam", "Yakobus", "Yak"]),
+    (
+        Book::FirstPeter,
+        &["1 Peter", "1 Pet", "1 Pe", "1 Petrus", "1 Ptr"],
+    ),
+    (
+        Book::SecondPeter,
+        &["2 Peter", "2 Pet", "2 Pe", "2 Petrus", "2 Ptr"],
+    ),
+    (
+        Book::FirstJohn,
+        &["1 John", "1 Jn", "1 Jhn", "1 Yohanes", "1 Yoh"],
+    ),
+    (
+        Book::SecondJohn,
+        &["2 John", "2 Jn", "2 Jhn", "2 Yohanes", "2 Yoh"],
+    ),
+    (
+        Book::ThirdJohn,
+        &["3 John", "3 Jn", "3 Jhn", "3 Yohanes", "3 Yoh"],
+    ),
+    (Book::Jude, &["Jude", "Jud", "Yudas", "Yud"]),
+    (
+        Book::Revelation,
+        &["Revelation", "Rev", "Re", "Wahyu", "Why"],
+    ),
+];
+
+lazy_static! {
+    static ref ALIASES: HashMap<String, Book> = BOOK_NAMES
+        .iter()
+        .flat_map(|(book, names)| names.iter().map(|name| (normalize(name), book.clone())))
+        .collect();
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReferenceError {
+    #[error("Unknown book \"{0}\"")]
+    UnknownBook(String),
+
+    #[error("Can't read \"{0}\" as a reference")]
+    Malformed(String),
+
+    #[error("{0} has no chapter {1}")]
+    NoSuchChapter(Book, i64),
+
+    #[error("Missing book name")]
+    MissingBook,
+}
+
+/// Lowercase and drop everything but letters and digits, with a leading
+/// roman numeral turned into a digit ("II Sam" -> "2sam").
+fn normalize(name: &str) -> String {
+    let name = name.trim();
+    let name = match name.split_once(|c: char| c.is_whitespace() || c == '.') {
+        Some(("III", rest)) => format!("3{rest}"),
+        Some(("II", rest)) => format!("2{rest}"),
+        Some(("I", rest)) => format!("1{rest}"),
+        _ => name.to_string(),
+    };
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Parse a book name or abbreviation in English or Indonesian. Unambiguous
+/// beginnings of a name ("Gene", "Kolo") are accepted too.
+pub fn parse_book(name: &str) -> Option<Book> {
+    let name = normalize(name);
+    if name.is_empty() {
+        return None;
+    }
+    if let Some(book) = ALIASES.get(&name) {
+        return Some(book.clone());
+    }
+
+    let mut matches = BOOK_NAMES
+        .iter()
+        .filter(|(_, names)| names.iter().any(|n| normalize(n).starts_with(&name)))
+        .map(|(book, _)| book);
+    match (matches.next(), matches.next()) {
+        (Some(book), None) if name.chars().filter(|c| c.is_alphabetic()).count() >= 3 => {
+            Some(book.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Parse references like "Yoh 3:16-18; Rm 8", "1 Kor 13", "Gen 1-3",
+/// "Mat 5:1-7:29", "Ps 23:1, 4" or "Jude 3". A reference without a book
+/// name continues the previous book: "Yoh 3:16; 4:1".
+pub fn parse_references(input: &str) -> Result<Vec<Passage>, ReferenceError> {
+    let mut passages = Vec::new();
+    let mut book: Option<Book> = None;
+
+    for segment in input.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        // The book name ends at the last letter: "1 Kor 13:4" -> ("1 Kor", " 13:4").
+        let split = segment
+            .char_indices()
+            .rfind(|(_, c)| c.is_alphabetic())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let (name, numbers) = segment.split_at(split);
+        if !name.trim().is_empty() {
+            book = Some(
+                parse_book(name)
+                    .ok_or_else(|| ReferenceError::UnknownBook(name.trim().to_string()))?,
+            );
+        }
+        let book = book.clone().ok_or(ReferenceError::MissingBook)?;
+        parse_numbers(&book, numbers, &mut passages).map_err(|e| match e {
+            ReferenceError::Malformed(_) => ReferenceError::Malformed(segment.to_string()),
+            e => e,
+        })?;
+    }
+
+    if passages.is_empty() {
+        return Err(ReferenceError::Malformed(input.trim().to_string()));
+    }
+    Ok(passages)
+}
+
+/// Parse the part after the book name, e.g. "3:16-18, 21", into `passages`.
+fn parse_numbers(
+    book: &Book,
+    numbers: &str,
+    passages: &mut Vec<Passage>,
+) -> Result<(), ReferenceError> {
+    let malformed = || ReferenceError::Malformed(numbers.to_string());
+    let numbers = numbers
+        .trim()
+        .trim_start_matches('.')
+        .replace(['–', '—'], "-")
+        .replace('.', ":");
+    let numbers = numbers.trim();
+    let single_chapter = book.total_chapters() == 1;
+
+    if numbers.is_empty() {
+        passages.push(Passage::chapter(book.clone(), 1));
+        return Ok(());
+    }
+
+    // Chapter of the previous part while it was a verse reference, so "3:16, 18"
+    // reads 18 as a verse of chapter 3.
+    let mut verse_chapter: Option<i64> = None;
+    for part in numbers.split(',').map(str::trim) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), Some(end.trim())),
+            None => (part, None),
+        };
+
+        let (chapter, verse) = match start.split_once(':') {
+            Some((c, v)) => (
+                parse_num(c).ok_or_else(malformed)?,
+                Some(parse_num(v).ok_or_else(malformed)?),
+            ),
+            None => {
+                let n = parse_num(start).ok_or_else(malformed)?;
+                match verse_chapter {
+                    Some(chapter) => (chapter, Some(n)),
+                    None if single_chapter => (1, Some(n)),
+                    None => (n, None),
+                }
+            }
+        };
+        let (end_chapter, end_verse) = match (end, verse) {
+            (None, _) => (chapter, verse),
+            (Some(end), _) if end.contains(':') => {
+                let (c, v) = end.split_once(':').expect("contains ':'");
+                (
+                    parse_num(c).ok_or_else(malformed)?,
+                    Some(parse_num(v).ok_or_else(malformed)?),
+                )
+            }
+            (Some(end), Some(_)) => (chapter, Some(parse_num(end).ok_or_else(malformed)?)),
+            (Some(end), None) => (parse_num(end).ok_or_else(malformed)?, None),
+        };
+
+        let total = book.total_chapters() as i64;
+        for c in [chapter, end_chapter] {
+            if c > total {
+                return Err(ReferenceError::NoSuchChapter(book.clone(), c));
+            }
+        }
+        let backwards = end_chapter < chapter
+            || (end_chapter == chapter
+                && matches!((verse, end_verse), (Some(v), Some(e)) if e < v));
+        // A verse range must end on a verse too: "3:16-4" is ambiguous.
+        if backwards || (verse.is_some() && end_verse.is_none()) {
+            return Err(malformed());
+        }
+
+        passages.push(Passage {
+            book: book.clone(),
+            chapter,
+            verse: verse.map(|v| v as u32),
+            end_chapter,
+            end_verse: end_verse.map(|v| v as u32),
+        });
+        verse_chapter = verse.map(|_| end_chapter);
+    }
+    Ok(())
+}
+
+fn parse_num(s: &str) -> Option<i64> {
+    s.trim().parse::<i64>().ok().filter(|n| *n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn book_names_and_abbreviations() {
+        assert_eq!(parse_book("Genesis"), Some(Book::Genesis));
+        assert_eq!(parse_book("kejadian"), Some(Book::Genesis));
+        assert_eq!(parse_book("1 Kor."), Some(Book::FirstCorinthians));
+        assert_eq!(parse_book("1kor"), Some(Book::FirstCorinthians));
+        assert_eq!(parse_book("II Sam"), Some(Book::SecondSamuel));
+        assert_eq!(parse_book("Hakim-hakim"), Some(Book::Judges));
+        assert_eq!(parse_book("Song of Songs"), Some(Book::SongOfSolomon));
+    }
+
+    #[test]
+    fn book_prefixes() {
+        assert_eq!(parse_book("Gene"), Some(Book::Genesis));
+        assert_eq!(parse_book("Kolo"), Some(Book::Colossians));
+        // Too short, or the beginning of several books.
+        assert_eq!(parse_book("Ge"), None);
+        assert_eq!(parse_book("Phi"), None);
+        assert_eq!(parse_book(""), None);
+        assert_eq!(parse_book("Foo"), None);
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(
+            parse_references("Yoh 3:16-18; Rm 8").unwrap(),
+            vec![
+                Passage::verses(Book::John, 3, 16, 18),
+                Passage::chapter(Book::Romans, 8),
+            ]
+        );
+        assert_eq!(
+            parse_references("1 Kor 13").unwrap(),
+            vec![Passage::chapter(Book::FirstCorinthians, 13)]
+        );
+        assert_eq!(
+            parse_references("Gen 1-3").unwrap(),
+            vec![Passage {
+                book: Book::Genesis,
+                chapter: 1,
+                verse: None,
+                end_chapter: 3,
+                end_verse: None,
+            }]
+        );
+        assert_eq!(
+            parse_references("Mat 5:1-7:29").unwrap(),
+            vec![Passage {
+                book: Book::Matthew,
+                chapter: 5,
+                verse: Some(1),
+                end_chapter: 7,
+                end_verse: Some(29),
+            }]
+        );
+        assert_eq!(
+            parse_references("Ps 23:1, 4").unwrap(),
+            vec![
+                Passage::verses(Book::Psalms, 23, 1, 1),
+                Passage::verses(Book::Psalms, 23, 4, 4),
+            ]
+        );
+        assert_eq!(
+            parse_references("Jude 3").unwrap(),
+            vec![Passage::verses(Book::Jude, 1, 3, 3)]
+        );
+        assert_eq!(
+            parse_references("Obaja").unwrap(),
+            vec![Passage::chapter(Book::Obadiah, 1)]
+        );
+    }
+
+    #[test]
+    fn references_continue_the_previous_book() {
+        assert_eq!(
+            parse_references("Yoh 3.16; 4:1").unwrap(),
+            vec![
+                Passage::verses(Book::John, 3, 16, 16),
+                Passage::verses(Book::John, 4, 1, 1),
+            ]
+        );
+        assert_eq!(parse_references("3:16"), Err(ReferenceError::MissingBook));
+    }
+
+    #[test]
+    fn invalid_references() {
+        assert_eq!(
+            parse_references("Foo 1"),
+            Err(ReferenceError::UnknownBook("Foo".to_string()))
+        );
+        assert_eq!(
+            parse_references("Gen 51"),
+            Err(ReferenceError::NoSuchChapter(Book::Genesis, 51))
+        );
+        assert_eq!(
+            parse_references("Gen 3:16-4"),
+            Err(ReferenceError::Malformed("Gen 3:16-4".to_string()))
+        );
+        assert_eq!(
+            parse_references("Gen 3-1"),
+            Err(ReferenceError::Malformed("Gen 3-1".to_string()))
+        );
+        assert_eq!(
+            parse_references("Gen 0"),
+            Err(ReferenceError::Malformed("Gen 0".to_string()))
+        );
+        assert_eq!(
+            parse_references(" ; "),
+            Err(ReferenceError::Malformed(";".to_string()))
+        );
+    }
+}
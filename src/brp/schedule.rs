@@ -0,0 +1,152 @@
+use super::{
+    model::{UserDates, UserReadings, UserSettings},
+    portion_label,
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        self,
+        hx::HxCfg,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg, ButtonType},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use chrono::{Duration, NaiveDate};
+use maud::{html, Markup, PreEscaped};
+use serde::Deserialize;
+
+/// Longest range shown at once, a bit over two years.
+const MAX_DAYS: i64 = 731;
+
+/// Page size and margins can't be set with utility classes.
+const PRINT_CSS: &str = r#"
+@page { size: A4 landscape; margin: 1cm; }
+@media print {
+    .schedule tr { break-inside: avoid; }
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+/// The schedule of every list, one row per day. Defaults to a year from the
+/// reading date.
+pub async fn page_schedule(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Query(query): Query<ScheduleQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let readings = UserReadings::from_user(&state.db, user.id).await;
+
+            let from = query.from.unwrap_or_else(|| dates.reading_date());
+            let to = query
+                .to
+                .filter(|to| *to >= from)
+                .unwrap_or(from + Duration::days(364))
+                .min(from + Duration::days(MAX_DAYS - 1));
+
+            Ok(view::pages::page(
+                locale.t("Reading schedule"),
+                page(&readings, &dates, (from, to), locale),
+            ))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+fn page(
+    readings: &UserReadings,
+    dates: &UserDates,
+    (from, to): (NaiveDate, NaiveDate),
+    locale: Locale,
+) -> Markup {
+    let reading_date = dates.reading_date();
+    let days = (to - from).num_days() + 1;
+
+    html! {
+        style { (PreEscaped(PRINT_CSS)) }
+        div class="flex justify-center" {
+            div class="flex flex-col gap-6 py-8 px-4 print:p-0 print:gap-2" {
+                div class="flex justify-between items-center gap-4 print:hidden" {
+                    h1 class="text-xl font-bold" { (locale.t("Reading schedule")) }
+                    (ui_button(html! { (locale.t("Back to readings")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).as_link("/"),
+                        &HxCfg::new()
+                    ))
+                }
+                form method="get" action="/schedule" class="flex gap-2 items-end print:hidden" {
+                    label class="flex flex-col gap-1 text-sm font-semibold" {
+                        (locale.t("From"))
+                        input type="date" name="from" value=(from.format("%Y-%m-%d"))
+                            class="text-sm rounded-xs border-border bg-background py-1.5";
+                    }
+                    label class="flex flex-col gap-1 text-sm font-semibold" {
+                        (locale.t("To"))
+                        input type="date" name="to" value=(to.format("%Y-%m-%d"))
+                            class="text-sm rounded-xs border-border bg-background py-1.5";
+                    }
+                    (ui_button(html! { (locale.t("Show")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).with_type(ButtonType::Submit),
+                        &HxCfg::new()
+                    ))
+                    (ui_button(html! { (locale.t("Print")) },
+                        &ButtonCfg::new().with_color(Color::Default).with_cn("ml-auto"),
+                        &HxCfg::new().with_script("on click call window.print()")
+                    ))
+                }
+                h1 class="hidden print:block font-bold" {
+                    (locale.t("Reading schedule")) ", "
+                    (from.format("%d/%m/%Y")) " – " (to.format("%d/%m/%Y"))
+                }
+                table class="schedule text-sm print:text-[8pt] border-collapse" {
+                    thead {
+                        tr class="border-b border-border text-left" {
+                            th class="py-1 pr-3" { (locale.t("Date")) }
+                            @for reading in &readings.readings {
+                                th class="py-1 pr-3 font-semibold" { (reading.label(locale)) }
+                            }
+                        }
+                    }
+                    tbody {
+                        @for day in 0..days {
+                            @let date = from + Duration::days(day);
+                            tr class="border-b border-border/50" {
+                                td class="py-1 pr-3 whitespace-nowrap tabular-nums" {
+                                    (date.format("%d/%m/%Y"))
+                                }
+                                @for reading in &readings.readings {
+                                    @let portion = reading.upcoming_portion(dates, (date - reading_date).num_days());
+                                    td class="py-1 pr-3 whitespace-nowrap" {
+                                        @if !portion.is_empty() {
+                                            label class="flex gap-1 items-center" {
+                                                input type="checkbox" class="rounded-xs print:border-black";
+                                                (portion_label(&portion, locale))
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use super::{
+    books::Book,
+    content::{BibleRegistry, BibleSource},
+    model::UserSettings,
+    PassageQuery,
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{self, pages::login::redirect_login},
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use maud::{html, Markup, PreEscaped};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+const MAX_HITS: i64 = 50;
+
+/// Markers passed to FTS5 `snippet()`, replaced by `<mark>` after escaping the verse text.
+pub const MATCH_START: &str = "\u{2}";
+pub const MATCH_END: &str = "\u{3}";
+
+#[derive(Debug)]
+pub struct SearchHit {
+    pub translation: String,
+    pub book: Book,
+    pub chapter: i64,
+    pub verse: i64,
+    /// Verse text around the match, with the matched terms between `MATCH_START` and `MATCH_END`.
+    pub snippet: String,
+}
+
+/// Turn user input into an FTS5 query: every word must appear, the last one
+/// as a prefix so results show up while typing. `None` if there's nothing to search.
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<_> = input
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\""))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+/// Best matching verses of the stored translations, or of `translation` only.
+pub async fn search_verses(
+    pool: &SqlitePool,
+    input: &str,
+    translation: Option<&str>,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    let Some(query) = fts_query(input) else {
+        return Ok(Vec::new());
+    };
+
+    let hits = sqlx::query!(
+        r#"SELECT v.translation, v.book, v.chapter, v.verse,
+            snippet(verses_fts, 0, ?1, ?2, '…', 24) AS "snippet!: String"
+        FROM verses_fts
+        JOIN verses v ON v.id = verses_fts.rowid
+        WHERE verses_fts MATCH ?3 AND (?4 IS NULL OR v.translation = ?4)
+        ORDER BY rank
+        LIMIT ?5"#,
+        MATCH_START,
+        MATCH_END,
+        query,
+        translation,
+        MAX_HITS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(hits
+        .into_iter()
+        .filter_map(|hit| {
+            Some(SearchHit {
+                book: Book::from_index(hit.book as usize)?,
+                translation: hit.translation,
+                chapter: hit.chapter,
+                verse: hit.verse,
+                snippet: hit.snippet,
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    /// Empty for all stored translations.
+    #[serde(default)]
+    translation: String,
+}
+
+/// Search form and results. Rendered into the reading pane when requested by htmx,
+/// as a page of its own otherwise.
+pub async fn get_search(
+    State(state): State<AppState>,
+    user: Option<User>,
+    headers: HeaderMap,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let stored = stored_translations(&state.bibles);
+            let settings = UserSettings::from_user(&state.db, user.id).await?;
+            let translation = if query.q.is_empty() && query.translation.is_empty() {
+                // Start with the user's translation if it can be searched.
+                settings
+                    .translation
+                    .filter(|code| stored.iter().any(|(c, _)| c == code))
+            } else {
+                Some(query.translation).filter(|code| !code.is_empty())
+            };
+            let hits = search_verses(&state.db, &query.q, translation.as_deref()).await?;
+
+            let content = fragment_search(
+                &stored,
+                &query.q,
+                translation.as_deref(),
+                &hits,
+                settings.locale,
+            );
+            if headers.contains_key("HX-Request") {
+                Ok(content)
+            } else {
+                Ok(view::pages::page(
+                    settings.locale.t("Search"),
+                    html! { div class="flex justify-center" { (content) } },
+                ))
+            }
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// (code, name) of the translations in the verse store; remote ones can't be searched.
+fn stored_translations(bibles: &BibleRegistry) -> Vec<(String, String)> {
+    bibles
+        .all()
+        .iter()
+        .filter(|t| matches!(t.source, BibleSource::Stored(_)))
+        .map(|t| (t.code.clone(), t.name.clone()))
+        .collect()
+}
+
+fn fragment_search(
+    stored: &[(String, String)],
+    q: &str,
+    translation: Option<&str>,
+    hits: &[SearchHit],
+    locale: Locale,
+) -> Markup {
+    html! {
+        div id="chapter-content" class="border border-border bg-background-100 w-[850px] pb-4 px-4 text-wrap max-h-screen h-screen flex flex-col" {
+            form class="flex gap-2 py-2 mt-2"
+                hx-get="/search"
+                hx-trigger="input changed delay:300ms from:find input, change from:find select, submit"
+                hx-target="#search-results"
+                hx-select="#search-results"
+                hx-swap="outerHTML"
+            {
+                input type="search" name="q" value=(q) placeholder=(locale.t("Search the Bible…")) autofocus
+                    class="flex-1 text-sm rounded-xs border-border bg-background py-1.5";
+                select name="translation" class="text-sm rounded-xs border-border bg-background py-1.5" {
+                    option value="" selected[translation.is_none()] { (locale.t("All translations")) }
+                    @for (code, name) in stored {
+                        option value=(code) selected[Some(code.as_str()) == translation] { (name) }
+                    }
+                }
+            }
+            (fragment_search_results(stored, q, hits, locale))
+        }
+    }
+}
+
+fn fragment_search_results(
+    stored: &[(String, String)],
+    q: &str,
+    hits: &[SearchHit],
+    locale: Locale,
+) -> Markup {
+    html! {
+        div id="search-results" class="overflow-y-auto flex-shrink border border-border p-4 scrollbar-thin scrollbar-thumb-foreground/70 scrollbar-track-foreground/10" {
+            @if stored.is_empty() {
+                p class="text-sm text-foreground/60" {
+                    (locale.t("Only imported translations can be searched, and there are none yet."))
+                }
+            } @else if q.trim().is_empty() {
+                p class="text-sm text-foreground/60" { (locale.t("Type a word or phrase to search.")) }
+            } @else if hits.is_empty() {
+                p class="text-sm text-foreground/60" { (locale.t("No verses found.")) }
+            } @else {
+                @if hits.len() as i64 == MAX_HITS {
+                    p class="text-xs text-foreground/60 mb-3" { (locale.t("Showing the first {n} verses.").replace("{n}", &MAX_HITS.to_string())) }
+                }
+                @for hit in hits {
+                    @let vals = serde_json::to_string(&PassageQuery {
+                        book: hit.book.index(),
+                        chapter: hit.chapter,
+                        verse: Some(hit.verse as u32),
+                        translation: Some(hit.translation.clone()),
+                    }).expect("serializable struct");
+                    button type="button"
+                        class="block w-full text-left mb-3 p-2 rounded-xs hover:bg-foreground/5"
+                        hx-get="/passage"
+                        hx-vals=(vals)
+                        hx-target="#chapter-content"
+                        hx-swap="outerHTML"
+                    {
+                        div class="flex gap-2 items-baseline text-sm font-semibold" {
+                            (hit.book.name(locale)) " " (hit.chapter) ":" (hit.verse)
+                            @if stored.len() > 1 {
+                                span class="text-xs font-normal text-foreground/50" { (hit.translation) }
+                            }
+                        }
+                        div class="text-sm" { (highlight_snippet(&hit.snippet)) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn highlight_snippet(snippet: &str) -> Markup {
+    let escaped = maud::html! { (snippet) }.into_string();
+    PreEscaped(
+        escaped
+            .replace(MATCH_START, "<mark class=\"bg-yellow-200\">")
+            .replace(MATCH_END, "</mark>"),
+    )
+}
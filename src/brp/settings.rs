@@ -0,0 +1,471 @@
+use super::{
+    books::Book,
+    calendar::{fragment_calendar, has_calendar_token},
+    export::fragment_import_message,
+    model::{Reading, UserReadings, UserSettings},
+    plan::{Plan, Rule},
+};
+use crate::{
+    api::tokens::{fragment_api_tokens, ApiToken},
+    auth::{
+        devices::fragment_devices,
+        session::{Device, SESSION_COOKIE},
+        User,
+    },
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        self,
+        hx::{HxCfg, HxHeaderBuilder, HxSwap},
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg, ButtonType},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, Form};
+use axum_extra::extract::PrivateCookieJar;
+use cookie::Cookie;
+use maud::{html, Markup, PreEscaped};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A reading list as edited on the settings page.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListForm {
+    /// Index of the list before editing, `None` for newly added lists.
+    from: Option<usize>,
+    books: Vec<String>,
+    /// Days to spread the list over, `None` for one chapter a day.
+    days: Option<i64>,
+    /// Read long chapters in sections, only used without `days`.
+    #[serde(default)]
+    sections: bool,
+}
+
+pub async fn page_settings(
+    State(state): State<AppState>,
+    user: Option<User>,
+    jar: PrivateCookieJar,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let readings = UserReadings::from_user(&state.db, user.id).await;
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let calendar = has_calendar_token(&state.db, user.id).await?;
+            let api_tokens = ApiToken::all_from_user(&state.db, user.id).await?;
+            let current = jar.get(SESSION_COOKIE);
+            let devices =
+                Device::all_from_user(&state.db, user.id, current.as_ref().map(Cookie::value))
+                    .await?;
+            Ok(view::pages::page(
+                locale.t("Settings"),
+                page(&readings, calendar, &api_tokens, &devices, locale),
+            ))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+fn page(
+    readings: &UserReadings,
+    calendar: bool,
+    api_tokens: &[ApiToken],
+    devices: &[Device],
+    locale: Locale,
+) -> Markup {
+    html! {
+        div class="flex justify-center" {
+            div class="flex flex-col gap-8 w-[900px] py-8 px-4" {
+                div class="flex justify-between items-center" {
+                    h1 class="text-xl font-bold" { (locale.t("Settings")) }
+                    (ui_button(html! { (locale.t("Back to readings")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).as_link("/"),
+                        &HxCfg::new()
+                    ))
+                }
+
+                (section_language(locale))
+                (section_readings(readings, locale))
+                (fragment_calendar(calendar, None, locale))
+                (fragment_api_tokens(api_tokens, None, locale))
+                (fragment_devices(devices, locale))
+                (section_export(locale))
+            }
+        }
+    }
+}
+
+fn section_language(locale: Locale) -> Markup {
+    html! {
+        section {
+            h2 class="font-bold text-md mb-1" { (locale.t("Language")) }
+            p class="text-sm text-foreground/60 mb-4" { (locale.t("Used for the interface and book names.")) }
+            form hx-post="/settings/locale" hx-trigger="change" {
+                select name="locale" class="text-sm rounded-xs border-border bg-background py-1.5" {
+                    @for l in Locale::all() {
+                        option value=(l.code()) selected[l == locale] { (l.name()) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn section_readings(readings: &UserReadings, locale: Locale) -> Markup {
+    let lists: Vec<_> = readings
+        .readings
+        .iter()
+        .enumerate()
+        .map(|(idx, reading)| ListForm {
+            from: Some(idx),
+            books: reading.books.iter().map(|b| b.to_string()).collect(),
+            days: match reading.rule {
+                Rule::Cycle | Rule::Sections => None,
+                Rule::Spread(days) => Some(days),
+            },
+            sections: reading.rule == Rule::Sections,
+        })
+        .collect();
+    let lists = serde_json::to_string(&lists).expect("serializable struct");
+    // Lists hold the stored (English) names, shown in the user's language.
+    let names: HashMap<_, _> = Book::all()
+        .iter()
+        .map(|book| (book.to_string(), book.name(locale)))
+        .collect();
+    let names = serde_json::to_string(&names).expect("serializable map");
+
+    html! {
+        section {
+            div class="flex justify-between items-baseline" {
+                h2 class="font-bold text-md mb-1" { (locale.t("Reading lists")) }
+                a href="/schedule" class="text-xs text-foreground/60 hover:underline" { (locale.t("Printable schedule")) }
+            }
+            p class="text-sm text-foreground/60 mb-4" {
+                (locale.t("Drag books between lists, reorder or remove lists, or start over from a plan template. Every list needs at least one book."))
+            }
+
+            script { (PreEscaped(READINGS_EDITOR_JS)) }
+            div x-data=(format!("readingsEditor({lists}, {names})")) class="flex flex-col gap-3" {
+                template x-for="(list, i) in lists" {
+                    div class="border border-border rounded-sm p-3 bg-background-100"
+                        "x-on:dragover.prevent"=""
+                        x-on:drop="drop(i, null)"
+                    {
+                        div class="flex justify-between items-center mb-2" {
+                            span class="font-semibold text-sm" x-text=(format!("`{} ${{i + 1}}`", locale.t("List"))) {}
+                            div class="flex gap-1 text-xs" {
+                                button type="button" class="px-2 py-1 hover:bg-foreground/5" x-on:click="move(i, -1)" { (locale.t("Up")) }
+                                button type="button" class="px-2 py-1 hover:bg-foreground/5" x-on:click="move(i, 1)" { (locale.t("Down")) }
+                                button type="button" class="px-2 py-1 text-red-600 hover:bg-red-50" x-on:click="removeList(i)" { (locale.t("Remove")) }
+                            }
+                        }
+                        label class="flex items-center gap-2 text-xs text-foreground/60 mb-2" {
+                            (locale.t("Finish in"))
+                            input type="number" min="1" x-model="list.days" placeholder="–"
+                                class="w-20 text-xs rounded-xs border-border bg-background py-1";
+                            (locale.t("days (leave empty for one chapter a day)"))
+                        }
+                        label class="flex items-center gap-2 text-xs text-foreground/60 mb-2"
+                            x-show="list.days === null || list.days === ''"
+                        {
+                            input type="checkbox" x-model="list.sections";
+                            (locale.t("Read Psalm 119 in sections"))
+                        }
+                        div class="flex flex-wrap gap-2 min-h-8" {
+                            template x-for="(book, j) in list.books" {
+                                span class="inline-flex items-center gap-1 text-xs border border-border rounded-xs px-2 py-1 cursor-move"
+                                    draggable="true"
+                                    x-on:dragstart="dragStart(i, j)"
+                                    "x-on:dragover.prevent"=""
+                                    "x-on:drop.stop"="drop(i, j)"
+                                {
+                                    span x-text="names[book] || book" {}
+                                    button type="button" class="text-foreground/50 hover:text-red-600" x-on:click="removeBook(i, j)" { "×" }
+                                }
+                            }
+                            select class="text-xs rounded-xs border-border bg-background py-1"
+                                x-on:change="addBook(i, $event.target.value); $event.target.value = ''"
+                            {
+                                option value="" { (locale.t("Add book…")) }
+                                @for book in Book::all() {
+                                    option value=(book) { (book.name(locale)) }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div class="flex gap-2 items-center" {
+                    (ui_button(html! { (locale.t("Add list")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).on_click("addList()"),
+                        &HxCfg::new()
+                    ))
+                    form hx-post="/settings/readings" hx-target="#settings-message" hx-swap="outerHTML" {
+                        input type="hidden" name="lists" x-bind:value="serialized()";
+                        (ui_button(html! { (locale.t("Save")) },
+                            &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit),
+                            &HxCfg::new()
+                        ))
+                    }
+                }
+                form class="flex gap-2 items-center" hx-post="/settings/readings/reset" {
+                    select name="plan" class="text-sm rounded-xs border-border bg-background py-1.5" {
+                        @for plan in Plan::all() {
+                            option value=(plan.slug()) { (locale.t(plan.name())) }
+                        }
+                    }
+                    (ui_button(html! { (locale.t("Reset to template")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).with_type(ButtonType::Submit),
+                        &HxCfg::new()
+                    ))
+                }
+                (fragment_message(None))
+            }
+        }
+    }
+}
+
+fn section_export(locale: Locale) -> Markup {
+    html! {
+        section {
+            h2 class="font-bold text-md mb-1" { (locale.t("Export and import")) }
+            p class="text-sm text-foreground/60 mb-4" {
+                (locale.t("Download your reading lists, progress, notes and journal, e.g. to move to another instance. Importing an export replaces all of your current data."))
+            }
+            div class="flex gap-2 mb-4" {
+                (ui_button(html! { (locale.t("Download export (JSON)")) },
+                    &ButtonCfg::new().with_color(Color::Alternative).as_link("/export"),
+                    &HxCfg::new()
+                ))
+                (ui_button(html! { (locale.t("Download reading log (CSV)")) },
+                    &ButtonCfg::new().with_color(Color::Alternative).as_link("/export/reading-log.csv"),
+                    &HxCfg::new()
+                ))
+            }
+            form class="flex gap-2 items-center"
+                hx-post="/import"
+                hx-encoding="multipart/form-data"
+                hx-target="#import-message"
+                hx-swap="outerHTML"
+                hx-confirm=(locale.t("Replace all of your current data with the export?"))
+            {
+                input type="file" name="archive" accept=".json,application/json" required class="text-sm";
+                (ui_button(html! { (locale.t("Import")) },
+                    &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit),
+                    &HxCfg::new()
+                ))
+            }
+            (fragment_import_message(None, locale))
+        }
+    }
+}
+
+/// `Err` messages are shown as validation errors.
+fn fragment_message(msg: Option<Result<&str, &str>>) -> Markup {
+    html! {
+        @match msg {
+            Some(Ok(msg)) => p id="settings-message" class="text-sm text-green-600" { (msg) },
+            Some(Err(msg)) => p id="settings-message" class="text-sm text-destructive" { (msg) },
+            None => p id="settings-message" class="text-sm invisible" { "message placeholder" },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveReadingsRequest {
+    lists: String,
+}
+
+pub async fn post_readings(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<SaveReadingsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let Ok(lists) = serde_json::from_str::<Vec<ListForm>>(&form.lists) else {
+                return Ok(fragment_message(Some(Err(
+                    locale.t("Malformed reading lists")
+                ))));
+            };
+            let current = UserReadings::from_user(&state.db, user.id).await;
+
+            let mut readings = Vec::with_capacity(lists.len());
+            let mut mapping = Vec::with_capacity(lists.len());
+            for (idx, list) in lists.into_iter().enumerate() {
+                let rule = match list.days {
+                    None if list.sections => Rule::Sections,
+                    None => Rule::Cycle,
+                    Some(days) if days > 0 => Rule::Spread(days),
+                    Some(_) => {
+                        return Ok(fragment_message(Some(Err(&locale
+                            .t("List {n} needs a positive number of days")
+                            .replace("{n}", &(idx + 1).to_string())))))
+                    }
+                };
+                let books = match parse_books(&list.books) {
+                    Ok(books) if !books.is_empty() => books,
+                    Ok(_) => {
+                        return Ok(fragment_message(Some(Err(&locale
+                            .t("List {n} is empty")
+                            .replace("{n}", &(idx + 1).to_string())))))
+                    }
+                    Err(book) => {
+                        return Ok(fragment_message(Some(Err(&locale
+                            .t("Unknown book \"{book}\"")
+                            .replace("{book}", book)))))
+                    }
+                };
+
+                // Keep the schedule of lists that were only moved or edited.
+                let from = list.from.filter(|i| *i < current.readings.len());
+                let reading = match from {
+                    Some(i) => Reading {
+                        books,
+                        rule,
+                        ..current.readings[i].clone()
+                    },
+                    None => Reading {
+                        rule,
+                        ..Reading::new(books)
+                    },
+                };
+                mapping.push(from);
+                readings.push(reading);
+            }
+            if readings.is_empty() {
+                return Ok(fragment_message(Some(Err(
+                    locale.t("Add at least one reading list")
+                ))));
+            }
+
+            UserReadings::with_readings(user.id, readings)
+                .replace_current_remapping(&state.db, &mapping)
+                .await?;
+
+            Ok(fragment_message(Some(Ok(locale.t("Saved")))))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+/// Parse book names, returning the first unknown one on failure.
+fn parse_books(names: &[String]) -> Result<Vec<Book>, &str> {
+    names
+        .iter()
+        .map(|name| name.parse::<Book>().map_err(|_| name.as_str()))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocaleRequest {
+    locale: String,
+}
+
+pub async fn post_locale(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<LocaleRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(user) = user {
+        if let Ok(locale) = form.locale.parse::<Locale>() {
+            UserSettings::set_locale(&state.db, user.id, locale).await?;
+        }
+    }
+
+    Ok(HxHeaderBuilder::new()
+        .with_swap(HxSwap::None)
+        .with_redirect("/settings")
+        .build())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetReadingsRequest {
+    plan: String,
+}
+
+pub async fn post_readings_reset(
+    State(state): State<AppState>,
+    user: Option<User>,
+    Form(form): Form<ResetReadingsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(user) = user {
+        let plan = form.plan.parse::<Plan>().unwrap_or(Plan::Horner);
+        let current = UserReadings::from_user(&state.db, user.id).await;
+        let default = UserReadings::with_readings(user.id, plan.readings());
+        let mapping: Vec<_> = default
+            .readings
+            .iter()
+            .map(|reading| {
+                current
+                    .readings
+                    .iter()
+                    .position(|r| r.books == reading.books)
+            })
+            .collect();
+
+        default
+            .replace_current_remapping(&state.db, &mapping)
+            .await?;
+    }
+
+    Ok(HxHeaderBuilder::new()
+        .with_swap(HxSwap::None)
+        .with_redirect("/settings")
+        .build())
+}
+
+const READINGS_EDITOR_JS: &str = r#"
+function readingsEditor(lists, names) {
+    return {
+        lists: lists,
+        names: names,
+        dragged: null,
+        dragStart(list, book) {
+            this.dragged = { list: list, book: book };
+        },
+        drop(list, before) {
+            if (this.dragged === null) return;
+            const [book] = this.lists[this.dragged.list].books.splice(this.dragged.book, 1);
+            const to = this.lists[list].books;
+            if (before === null) {
+                to.push(book);
+            } else {
+                if (this.dragged.list === list && this.dragged.book < before) before -= 1;
+                to.splice(before, 0, book);
+            }
+            this.dragged = null;
+        },
+        move(i, delta) {
+            const j = i + delta;
+            if (j < 0 || j >= this.lists.length) return;
+            const [list] = this.lists.splice(i, 1);
+            this.lists.splice(j, 0, list);
+        },
+        addList() {
+            this.lists.push({ from: null, books: [], days: null, sections: false });
+        },
+        serialized() {
+            return JSON.stringify(this.lists.map((list) => ({
+                from: list.from,
+                books: list.books,
+                days: list.days === "" || list.days === null ? null : Number(list.days),
+                sections: !!list.sections,
+            })));
+        },
+        removeList(i) {
+            this.lists.splice(i, 1);
+        },
+        addBook(i, book) {
+            if (book) this.lists[i].books.push(book);
+        },
+        removeBook(i, j) {
+            this.lists[i].books.splice(j, 1);
+        },
+    };
+}
+"#;
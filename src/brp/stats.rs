@@ -0,0 +1,271 @@
+use super::{
+    books::Book,
+    model::{UserDates, UserReadings, UserSettings},
+};
+use crate::{
+    auth::User,
+    errors::ApiError,
+    i18n::Locale,
+    view::{
+        self,
+        hx::HxCfg,
+        pages::login::redirect_login,
+        ui::{
+            button::{ui_button, ButtonCfg},
+            Color,
+        },
+    },
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse};
+use chrono::{Datelike, Duration, NaiveDate};
+use maud::{html, Markup};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug)]
+pub struct ReadRecord {
+    pub reading_idx: usize,
+    pub book: Book,
+    pub chapter: i64,
+    pub read_on: NaiveDate,
+}
+
+#[derive(Debug)]
+pub struct BookStat {
+    pub book: Book,
+    pub read: u16,
+    pub total: u16,
+}
+
+impl BookStat {
+    pub fn percentage(&self) -> u16 {
+        self.read * 100 / self.total
+    }
+}
+
+/// Reading statistics computed from the `progress` table.
+#[derive(Debug)]
+pub struct UserStats {
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    /// Chapters read per reading list, aligned with `UserReadings.readings`.
+    pub per_list: Vec<usize>,
+    pub per_book: Vec<BookStat>,
+    /// Number of chapters read on each date.
+    pub per_day: BTreeMap<NaiveDate, usize>,
+}
+
+impl UserStats {
+    pub async fn from_user(
+        pool: &SqlitePool,
+        user_id: i64,
+        lists: usize,
+        today: NaiveDate,
+    ) -> Result<Self, sqlx::Error> {
+        let records = sqlx::query!(
+            "SELECT reading_idx, book, chapter, read_on FROM progress WHERE user_id = ?",
+            user_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rec| {
+            Some(ReadRecord {
+                reading_idx: rec.reading_idx as usize,
+                book: rec.book.parse::<Book>().ok()?,
+                chapter: rec.chapter,
+                read_on: rec.read_on,
+            })
+        })
+        .collect::<Vec<_>>();
+
+        Ok(Self::compute(&records, lists, today))
+    }
+
+    pub fn compute(records: &[ReadRecord], lists: usize, today: NaiveDate) -> Self {
+        let mut per_list = vec![0; lists];
+        let mut per_day = BTreeMap::new();
+        let mut chapters: HashMap<Book, HashSet<i64>> = HashMap::new();
+
+        for rec in records {
+            if let Some(count) = per_list.get_mut(rec.reading_idx) {
+                *count += 1;
+            }
+            *per_day.entry(rec.read_on).or_insert(0) += 1;
+            chapters
+                .entry(rec.book.clone())
+                .or_default()
+                .insert(rec.chapter);
+        }
+
+        let per_book = Book::all()
+            .into_iter()
+            .map(|book| BookStat {
+                read: chapters.get(&book).map_or(0, |c| c.len() as u16),
+                total: book.total_chapters(),
+                book,
+            })
+            .collect();
+
+        let days: BTreeSet<_> = per_day.keys().cloned().collect();
+        let (current_streak, longest_streak) = streaks(&days, today);
+
+        Self {
+            current_streak,
+            longest_streak,
+            per_list,
+            per_book,
+            per_day,
+        }
+    }
+}
+
+/// (current, longest) run of consecutive days with at least one chapter read.
+/// The current streak is still alive if the last read was yesterday.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (i64, i64) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for day in days {
+        run = match prev {
+            Some(p) if *day - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(*day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    (current, longest)
+}
+
+pub async fn page_stats(
+    State(state): State<AppState>,
+    user: Option<User>,
+) -> Result<impl IntoResponse, ApiError> {
+    match user {
+        Some(user) => {
+            let dates = UserDates::from_user_or_set_default(&state.db, user.id).await;
+            let readings = UserReadings::from_user(&state.db, user.id).await;
+            let locale = UserSettings::from_user(&state.db, user.id).await?.locale;
+            let today = dates.reading_date();
+            let stats =
+                UserStats::from_user(&state.db, user.id, readings.readings.len(), today).await?;
+
+            Ok(view::pages::page(
+                locale.t("Statistics"),
+                page(&readings, &stats, today, locale),
+            ))
+        }
+        None => Ok(redirect_login()),
+    }
+}
+
+fn page(readings: &UserReadings, stats: &UserStats, today: NaiveDate, locale: Locale) -> Markup {
+    html! {
+        div class="flex justify-center" {
+            div class="flex flex-col gap-8 w-[900px] py-8 px-4" {
+                div class="flex justify-between items-center" {
+                    h1 class="text-xl font-bold" { (locale.t("Statistics")) }
+                    (ui_button(html! { (locale.t("Back to readings")) },
+                        &ButtonCfg::new().with_color(Color::Alternative).as_link("/"),
+                        &HxCfg::new()
+                    ))
+                }
+
+                div class="grid grid-cols-2 gap-4" {
+                    (stat_card(locale.t("Current streak"), stats.current_streak, locale))
+                    (stat_card(locale.t("Longest streak"), stats.longest_streak, locale))
+                }
+
+                section {
+                    h2 class="font-bold text-md mb-3" { (locale.t("Chapters read per list")) }
+                    div class="flex flex-col gap-1" {
+                        @for (reading, count) in readings.readings.iter().zip(stats.per_list.iter()) {
+                            div class="flex justify-between text-sm border-b border-border py-1" {
+                                span { (reading.label(locale)) }
+                                span class="font-semibold" { (count) }
+                            }
+                        }
+                    }
+                }
+
+                section {
+                    h2 class="font-bold text-md mb-3" { (locale.t("Books completed")) }
+                    div class="grid grid-cols-3 gap-x-6 gap-y-2" {
+                        @for stat in &stats.per_book {
+                            div class="text-xs" {
+                                div class="flex justify-between" {
+                                    span { (stat.book.name(locale)) }
+                                    span class="text-foreground/60" { (stat.percentage()) "%" }
+                                }
+                                div class="h-1.5 w-full bg-foreground/10 rounded-sm" {
+                                    div class="h-1.5 bg-green-600 rounded-sm"
+                                        style=(format!("width: {}%", stat.percentage())) {}
+                                }
+                            }
+                        }
+                    }
+                }
+
+                section {
+                    h2 class="font-bold text-md mb-3" { (today.year()) }
+                    (heatmap(&stats.per_day, today.year(), locale))
+                }
+            }
+        }
+    }
+}
+
+fn stat_card(label: &str, days: i64, locale: Locale) -> Markup {
+    html! {
+        div class="border border-border rounded-sm p-4 bg-background-100" {
+            div class="text-sm text-foreground/60" { (label) }
+            div class="text-2xl font-bold" {
+                (days) " " (locale.t(if days == 1 { "day" } else { "days" }))
+            }
+        }
+    }
+}
+
+/// GitHub-style calendar of the year, one column per week starting on Monday.
+fn heatmap(per_day: &BTreeMap<NaiveDate, usize>, year: i32, locale: Locale) -> Markup {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).expect("valid year, month, day");
+    let last = NaiveDate::from_ymd_opt(year, 12, 31).expect("valid year, month, day");
+    let padding = first.weekday().num_days_from_monday();
+
+    html! {
+        div class="grid grid-flow-col grid-rows-7 gap-1 w-fit" {
+            @for _ in 0..padding {
+                div class="w-3 h-3" {}
+            }
+            @for day in first.iter_days().take_while(|d| *d <= last) {
+                @let count = per_day.get(&day).copied().unwrap_or(0);
+                div class=(format!("w-3 h-3 rounded-xs {}", heat_cn(count)))
+                    title=(locale.t("{date}: {n} chapters")
+                        .replace("{date}", &day.format("%d/%m/%Y").to_string())
+                        .replace("{n}", &count.to_string())) {}
+            }
+        }
+    }
+}
+
+fn heat_cn(count: usize) -> &'static str {
+    match count {
+        0 => "bg-foreground/10",
+        1..=3 => "bg-green-300",
+        4..=6 => "bg-green-500",
+        _ => "bg-green-700",
+    }
+}
@@ -2,12 +2,14 @@ use axum::{http::StatusCode, response::IntoResponse, response::Response};
 use maud::html;
 use thiserror::Error;
 
-use crate::brp::content;
+use crate::{brp::content, mail, view};
 
 #[derive(Debug, Error)]
 pub enum ApiError {
     #[error("SQL error: {0}")]
     SQL(#[from] sqlx::Error),
+    #[error("Mail error: {0}")]
+    Mail(#[from] mail::MailError),
     #[error("HTTP request error: {0}")]
     Request(#[from] reqwest::Error),
     #[error("OAuth token error: {0}")]
@@ -20,6 +22,10 @@ pub enum ApiError {
     ),
     #[error("You're not authorized!")]
     Unauthorized,
+    /// The login provider sent the user back without a valid login, e.g. a
+    /// forged or expired callback.
+    #[error("Login failed")]
+    LoginFailed,
     #[error("Attempted to get a non-none value but found none")]
     OptionError,
     #[error("Attempted to parse a number to an integer but errored out: {0}")]
@@ -35,6 +41,7 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         match self {
             Self::SQL(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            Self::Mail(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
             Self::Request(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
             Self::TokenError(e) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
@@ -42,6 +49,22 @@ impl IntoResponse for ApiError {
             Self::Unauthorized => {
                 (StatusCode::UNAUTHORIZED, "Unauthorized!".to_string()).into_response()
             }
+            Self::LoginFailed => (
+                StatusCode::BAD_REQUEST,
+                view::pages::page(
+                    "Login failed",
+                    html! {
+                        div class="flex flex-col justify-center items-center h-screen gap-4" {
+                            h1 class="text-xl font-bold" { "Login failed" }
+                            p class="text-sm text-foreground/60" {
+                                "The login could not be completed, it may have expired. Please try again."
+                            }
+                            a href="/login" class="text-sm hover:underline" { "Back to login" }
+                        }
+                    },
+                ),
+            )
+                .into_response(),
             Self::OptionError => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Attempted to get a non-none value but found none".to_string(),
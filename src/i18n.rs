@@ -0,0 +1,302 @@
+use chrono::{Datelike, NaiveDate};
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Language of the interface and of book names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    Id,
+}
+
+impl Locale {
+    pub fn all() -> [Locale; 2] {
+        [Locale::En, Locale::Id]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Id => "id",
+        }
+    }
+
+    /// Name of the language in the language itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Id => "Bahasa Indonesia",
+        }
+    }
+
+    /// `text` in this locale. The English text is the key, and is returned as is
+    /// when there is no translation. Placeholders like `{n}` are left for the
+    /// caller to replace.
+    pub fn t(&self, text: &'static str) -> &'static str {
+        match self {
+            Locale::En => text,
+            Locale::Id => INDONESIAN.get(text).copied().unwrap_or(text),
+        }
+    }
+
+    /// e.g. "Sunday, 5 May 2024" or "Minggu, 5 Mei 2024".
+    pub fn long_date(&self, date: NaiveDate) -> String {
+        match self {
+            Locale::En => date.format("%A, %-d %B %Y").to_string(),
+            Locale::Id => format!(
+                "{}, {} {} {}",
+                INDONESIAN_WEEKDAYS[date.weekday().num_days_from_monday() as usize],
+                date.day(),
+                INDONESIAN_MONTHS[date.month0() as usize],
+                date.year()
+            ),
+        }
+    }
+}
+
+const INDONESIAN_WEEKDAYS: [&str; 7] = [
+    "Senin", "Selasa", "Rabu", "Kamis", "Jumat", "Sabtu", "Minggu",
+];
+
+const INDONESIAN_MONTHS: [&str; 12] = [
+    "Januari",
+    "Februari",
+    "Maret",
+    "April",
+    "Mei",
+    "Juni",
+    "Juli",
+    "Agustus",
+    "September",
+    "Oktober",
+    "November",
+    "Desember",
+];
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Locale::all()
+            .into_iter()
+            .find(|locale| locale.code() == s)
+            .ok_or("unknown locale")
+    }
+}
+
+lazy_static! {
+    static ref INDONESIAN: HashMap<&'static str, &'static str> = HashMap::from([
+        // Readings page
+        ("Start date", "Tanggal mulai"),
+        ("Currently reading", "Sedang dibaca"),
+        ("Go to, e.g. Yoh 3:16-18; Rm 8", "Buka, mis. Yoh 3:16-18; Rm 8"),
+        ("Go", "Buka"),
+        ("Search", "Cari"),
+        ("Statistics", "Statistik"),
+        ("Settings", "Pengaturan"),
+        ("Logout", "Keluar"),
+        ("Readings", "Bacaan"),
+        ("{done}/{total} read", "{done}/{total} dibaca"),
+        ("paused", "dijeda"),
+        ("{n} days behind", "tertinggal {n} hari"),
+        ("List settings", "Pengaturan daftar"),
+        ("Mark as read", "Tandai sudah dibaca"),
+        ("Mark as unread", "Tandai belum dibaca"),
+        ("Rest day", "Hari istirahat"),
+        ("Nothing to read in this list today.", "Tidak ada bacaan dalam daftar ini hari ini."),
+        ("Read side by side with another translation", "Baca berdampingan dengan terjemahan lain"),
+        ("No parallel", "Tanpa paralel"),
+        ("Error", "Kesalahan"),
+        ("Internal Server Error", "Kesalahan Server"),
+        ("Error fetching chapter content", "Gagal mengambil isi pasal"),
+        ("Unknown translation", "Terjemahan tidak dikenal"),
+        ("Unknown reading list", "Daftar bacaan tidak dikenal"),
+        ("Unknown passage", "Perikop tidak dikenal"),
+        // List settings modal
+        ("Day {n}", "Hari {n}"),
+        (" (paused)", " (dijeda)"),
+        ("Leave empty to follow the global start date.", "Kosongkan untuk mengikuti tanggal mulai umum."),
+        ("Paused", "Dijeda"),
+        (
+            "The list stays on its current chapter until resumed. Other lists are not affected.",
+            "Daftar tetap pada pasalnya sampai dilanjutkan. Daftar lain tidak terpengaruh.",
+        ),
+        ("Catch-up mode", "Mode mengejar"),
+        (
+            "The list only advances when its chapter is marked as read, so missed days are not skipped.",
+            "Daftar hanya maju setelah pasalnya ditandai sudah dibaca, jadi hari yang terlewat tidak dilompati.",
+        ),
+        ("Save", "Simpan"),
+        ("Restart from the beginning", "Mulai ulang dari awal"),
+        ("Cancel", "Batal"),
+        // Onboarding and plans
+        ("Welcome", "Selamat datang"),
+        ("Choose a reading plan", "Pilih rencana bacaan"),
+        (
+            "The lists can be customised or replaced by another plan later in the settings.",
+            "Daftar bacaan dapat diubah atau diganti dengan rencana lain nanti di pengaturan.",
+        ),
+        ("Start reading", "Mulai membaca"),
+        ("Chronological", "Kronologis"),
+        ("New Testament in a year", "Perjanjian Baru dalam setahun"),
+        ("Psalms & Proverbs monthly", "Mazmur & Amsal setiap bulan"),
+        (
+            "Ten lists, one chapter from each every day. Each list starts over when finished.",
+            "Sepuluh daftar, satu pasal dari masing-masing setiap hari. Setiap daftar diulang dari awal setelah selesai.",
+        ),
+        (
+            "Four lists (Old Testament history, New Testament, wisdom and prophets, Psalms), one chapter from each every day and Psalm 119 in sections.",
+            "Empat daftar (sejarah Perjanjian Lama, Perjanjian Baru, hikmat dan nabi-nabi, Mazmur), satu pasal dari masing-masing setiap hari dan Mazmur 119 per bagian.",
+        ),
+        (
+            "The whole Bible in the order the events happened, in a year.",
+            "Seluruh Alkitab menurut urutan peristiwanya, dalam setahun.",
+        ),
+        (
+            "Matthew to Revelation in a year, with the occasional rest day.",
+            "Matius sampai Wahyu dalam setahun, dengan sesekali hari istirahat.",
+        ),
+        (
+            "All the Psalms every 30 days and Proverbs every 31 days.",
+            "Seluruh Mazmur setiap 30 hari dan Amsal setiap 31 hari.",
+        ),
+        // Settings page
+        ("Back to readings", "Kembali ke bacaan"),
+        ("Language", "Bahasa"),
+        ("Used for the interface and book names.", "Dipakai untuk antarmuka dan nama kitab."),
+        ("Reading lists", "Daftar bacaan"),
+        (
+            "Drag books between lists, reorder or remove lists, or start over from a plan template. Every list needs at least one book.",
+            "Seret kitab antar daftar, ubah urutan atau hapus daftar, atau mulai lagi dari templat rencana. Setiap daftar memerlukan setidaknya satu kitab.",
+        ),
+        ("List", "Daftar"),
+        ("Up", "Naik"),
+        ("Down", "Turun"),
+        ("Remove", "Hapus"),
+        ("Finish in", "Selesai dalam"),
+        ("days (leave empty for one chapter a day)", "hari (kosongkan untuk satu pasal sehari)"),
+        ("Read Psalm 119 in sections", "Baca Mazmur 119 per bagian"),
+        ("Add book…", "Tambah kitab…"),
+        ("Add list", "Tambah daftar"),
+        ("Reset to template", "Kembalikan ke templat"),
+        ("Malformed reading lists", "Daftar bacaan tidak valid"),
+        ("List {n} needs a positive number of days", "Daftar {n} memerlukan jumlah hari yang positif"),
+        ("List {n} is empty", "Daftar {n} kosong"),
+        ("Unknown book \"{book}\"", "Kitab \"{book}\" tidak dikenal"),
+        ("Add at least one reading list", "Tambahkan setidaknya satu daftar bacaan"),
+        ("Saved", "Tersimpan"),
+        ("Calendar feed", "Umpan kalender"),
+        (
+            "Subscribe to this address in a calendar app to see the readings of the coming days. Anyone who has the address can see them, so revoke it if it leaks.",
+            "Langgani alamat ini di aplikasi kalender untuk melihat bacaan hari-hari mendatang. Siapa pun yang memiliki alamat ini dapat melihatnya, jadi cabut jika bocor.",
+        ),
+        ("New address", "Alamat baru"),
+        ("Revoke", "Cabut"),
+        ("Create address", "Buat alamat"),
+        ("Bible reading", "Bacaan Alkitab"),
+        ("API tokens", "Token API"),
+        (
+            "Tokens let scripts and other apps use the JSON API on your behalf, sent as an \"Authorization: Bearer\" header.",
+            "Token memungkinkan skrip dan aplikasi lain memakai API JSON atas nama Anda, dikirim sebagai header \"Authorization: Bearer\".",
+        ),
+        ("Copy the token now, it won't be shown again.", "Salin token sekarang, token tidak akan ditampilkan lagi."),
+        ("Copy the address now, it won't be shown again.", "Salin alamat sekarang, alamat tidak akan ditampilkan lagi."),
+        ("Name", "Nama"),
+        ("Access", "Akses"),
+        ("Created", "Dibuat"),
+        ("Last used", "Terakhir dipakai"),
+        ("Never", "Belum pernah"),
+        ("Revoke this token? Apps using it stop working.", "Cabut token ini? Aplikasi yang memakainya akan berhenti bekerja."),
+        ("Token name, e.g. Phone widget", "Nama token, mis. Widget ponsel"),
+        ("Read only", "Hanya baca"),
+        ("Read and write", "Baca dan tulis"),
+        ("Create token", "Buat token"),
+        ("Devices", "Perangkat"),
+        ("Browsers where you're logged in. Log out any you don't recognise.", "Peramban tempat Anda masuk. Keluarkan yang tidak Anda kenali."),
+        ("Device", "Perangkat"),
+        ("Logged in", "Masuk"),
+        ("Last seen", "Terakhir aktif"),
+        ("Unknown device", "Perangkat tidak dikenal"),
+        ("This device", "Perangkat ini"),
+        ("Log out", "Keluar"),
+        ("Log out other devices", "Keluarkan perangkat lain"),
+        ("Log out everywhere", "Keluar dari semua perangkat"),
+        ("Export and import", "Ekspor dan impor"),
+        (
+            "Download your reading lists, progress, notes and journal, e.g. to move to another instance. Importing an export replaces all of your current data.",
+            "Unduh daftar bacaan, kemajuan, catatan dan jurnal Anda, mis. untuk pindah ke server lain. Mengimpor hasil ekspor akan mengganti semua data Anda saat ini.",
+        ),
+        ("Download export (JSON)", "Unduh ekspor (JSON)"),
+        ("Download reading log (CSV)", "Unduh catatan bacaan (CSV)"),
+        ("Replace all of your current data with the export?", "Ganti semua data Anda saat ini dengan hasil ekspor?"),
+        ("Import", "Impor"),
+        ("Imported.", "Berhasil diimpor."),
+        ("Choose an export file to import.", "Pilih berkas ekspor untuk diimpor."),
+        ("The file could not be imported:", "Berkas tidak dapat diimpor:"),
+        ("Printable schedule", "Jadwal untuk dicetak"),
+        // Reading schedule
+        ("Reading schedule", "Jadwal bacaan"),
+        ("From", "Dari"),
+        ("To", "Sampai"),
+        ("Show", "Tampilkan"),
+        ("Print", "Cetak"),
+        ("Date", "Tanggal"),
+        // Statistics page
+        ("Current streak", "Beruntun saat ini"),
+        ("Longest streak", "Beruntun terpanjang"),
+        ("Chapters read per list", "Pasal yang dibaca per daftar"),
+        ("Books completed", "Kitab yang diselesaikan"),
+        ("day", "hari"),
+        ("days", "hari"),
+        ("{date}: {n} chapters", "{date}: {n} pasal"),
+        // Highlights, bookmarks and notes
+        ("My notes", "Catatan saya"),
+        ("Notes", "Catatan"),
+        ("Bookmarks", "Penanda"),
+        ("Highlights", "Sorotan"),
+        (
+            "Nothing here yet. Click a verse number while reading to highlight, bookmark or write a note.",
+            "Belum ada apa-apa. Klik nomor ayat saat membaca untuk menyorot, menandai atau menulis catatan.",
+        ),
+        ("Select verse, shift-click to select a range", "Pilih ayat, shift-klik untuk memilih rentang"),
+        ("Yellow", "Kuning"),
+        ("Green", "Hijau"),
+        ("Blue", "Biru"),
+        ("Pink", "Merah muda"),
+        ("Clear", "Hapus sorotan"),
+        ("Bookmark", "Penanda"),
+        ("Note", "Catatan"),
+        ("Write a note, Markdown is supported…", "Tulis catatan, Markdown didukung…"),
+        ("Save note", "Simpan catatan"),
+        ("Edit", "Ubah"),
+        ("Delete", "Hapus"),
+        // Journal
+        ("Journal", "Jurnal"),
+        ("History", "Riwayat"),
+        ("What struck you in today's reading?", "Apa yang berkesan dari bacaan hari ini?"),
+        ("Search your journal…", "Cari di jurnal…"),
+        ("No journal entries yet.", "Belum ada catatan jurnal."),
+        ("No entries found.", "Tidak ada catatan yang ditemukan."),
+        // Search
+        ("Search the Bible…", "Cari di Alkitab…"),
+        ("All translations", "Semua terjemahan"),
+        (
+            "Only imported translations can be searched, and there are none yet.",
+            "Hanya terjemahan yang diimpor yang dapat dicari, dan belum ada satu pun.",
+        ),
+        ("Type a word or phrase to search.", "Ketik kata atau frasa untuk dicari."),
+        ("No verses found.", "Tidak ada ayat yang ditemukan."),
+        ("Showing the first {n} verses.", "Menampilkan {n} ayat pertama."),
+    ]);
+}
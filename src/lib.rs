@@ -1,10 +1,15 @@
+use auth::providers::IdentityProviders;
 use axum::extract::FromRef;
+use brp::content::BibleRegistry;
 use cookie::Key;
 use sqlx::SqlitePool;
 
+pub mod api;
 pub mod auth;
 pub mod brp;
 pub mod errors;
+pub mod i18n;
+pub mod mail;
 pub mod utils;
 pub mod view;
 
@@ -12,6 +17,8 @@ pub mod view;
 pub struct AppState {
     pub db: SqlitePool,
     pub key: Key,
+    pub bibles: BibleRegistry,
+    pub providers: IdentityProviders,
 }
 
 impl FromRef<AppState> for Key {
@@ -0,0 +1,140 @@
+//! Sending email, e.g. login links. The transport is picked with
+//! `MAIL_TRANSPORT`, see `.env.example`.
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::{path::PathBuf, sync::Arc};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("invalid email address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+    #[error("building the message: {0}")]
+    Message(#[from] lettre::error::Error),
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("writing the message: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A plain text email.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[axum::async_trait]
+pub trait MailTransport: Send + Sync {
+    async fn send(&self, mail: &Mail) -> Result<(), MailError>;
+}
+
+/// The transport configured by environment variables, if any.
+pub fn transport_from_env() -> Option<Arc<dyn MailTransport>> {
+    let transport: Arc<dyn MailTransport> = match std::env::var("MAIL_TRANSPORT").as_deref() {
+        Ok("smtp") => Arc::new(SmtpTransport::from_env()),
+        Ok("file") => Arc::new(FileTransport::new(
+            std::env::var("MAIL_DIR").unwrap_or_else(|_| "./mail".to_string()),
+        )),
+        Ok("log") => Arc::new(LogTransport),
+        Ok(other) => panic!("unknown MAIL_TRANSPORT \"{other}\", use smtp, file or log"),
+        Err(_) => return None,
+    };
+    Some(transport)
+}
+
+/// Sends mail through an SMTP server with STARTTLS.
+pub struct SmtpTransport {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name).unwrap_or_else(|_| panic!("{name} env var must be set"))
+        };
+        let host = var("SMTP_HOST");
+        let port = std::env::var("SMTP_PORT")
+            .ok()
+            .map(|port| port.parse().expect("SMTP_PORT must be a port number"))
+            .unwrap_or(587);
+
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .expect("valid SMTP_HOST")
+            .port(port);
+        if let (Ok(username), Ok(password)) = (
+            std::env::var("SMTP_USERNAME"),
+            std::env::var("SMTP_PASSWORD"),
+        ) {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+
+        Self {
+            from: var("MAIL_FROM")
+                .parse()
+                .expect("MAIL_FROM must be an email address"),
+            transport: transport.build(),
+        }
+    }
+}
+
+#[axum::async_trait]
+impl MailTransport for SmtpTransport {
+    async fn send(&self, mail: &Mail) -> Result<(), MailError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse()?)
+            .subject(mail.subject.clone())
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Writes every mail to a file in a directory, for development.
+pub struct FileTransport {
+    dir: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[axum::async_trait]
+impl MailTransport for FileTransport {
+    async fn send(&self, mail: &Mail) -> Result<(), MailError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let name = format!(
+            "{}-{}.txt",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            mail.to.replace(['/', '\\'], "_")
+        );
+        let content = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            mail.to, mail.subject, mail.body
+        );
+        tokio::fs::write(self.dir.join(name), content).await?;
+        Ok(())
+    }
+}
+
+/// Only logs the recipient and subject, to audit that mail went out. The body
+/// may hold a login link and is never logged, `FileTransport` is the sink to
+/// read mail in development and tests.
+pub struct LogTransport;
+
+#[axum::async_trait]
+impl MailTransport for LogTransport {
+    async fn send(&self, mail: &Mail) -> Result<(), MailError> {
+        tracing::info!("mail to {}: {}", mail.to, mail.subject);
+        Ok(())
+    }
+}
@@ -1,16 +1,17 @@
 use axum::{
-    handler::Handler,
     routing::{get, post},
-    Extension, Router,
+    Router,
 };
 use brp_web::{
-    auth::{self, GOOGLE_OAUTH_CLIENT_ID, GOOGLE_OAUTH_CLIENT_SECRET},
-    brp,
+    api,
+    auth::{self, providers::IdentityProviders},
+    brp::{self, content::BibleRegistry},
+    mail,
+    utils::open_database,
     view::pages::login,
     AppState,
 };
 use cookie::Key;
-use sqlx::sqlite::SqlitePoolOptions;
 use tokio::net::TcpListener;
 use tower_http::{services::ServeDir, trace::TraceLayer};
 // #[cfg(debug_assertions)]
@@ -29,40 +30,111 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let sqlite_pool = SqlitePoolOptions::new()
-        .connect("./database.sqlite")
+    let sqlite_pool = open_database()
         .await
-        .unwrap();
+        .expect("open and migrate the database");
+
+    auth::session::spawn_session_sweeper(sqlite_pool.clone());
+
+    // Translations imported with `import-bible` are picked up on startup.
+    let bibles = BibleRegistry::load(&sqlite_pool, std::env::var("BIBLE_TRANSLATION").ok())
+        .await
+        .expect("readable translations table");
 
     let state = AppState {
         db: sqlite_pool,
+        bibles,
+        providers: IdentityProviders::from_env(mail::transport_from_env()).await,
         key: Key::from(
             &hex::decode(auth::JWT_SECRET.as_str())
                 .expect("valid hex string with minimum bytes 64"),
         ),
     };
     let brp_router = Router::new()
+        .route("/", get(brp::page_brp))
+        .route("/dates", post(brp::post_dates))
+        .route("/progress", post(brp::post_progress))
         .route(
-            "/",
-            get(brp::page_brp.layer(Extension(GOOGLE_OAUTH_CLIENT_ID.as_str()))),
+            "/readings/:idx",
+            get(brp::get_reading_settings).post(brp::post_reading_settings),
         )
-        .route("/dates", post(brp::post_dates))
-        .route("/q", get(brp::get_q_chapter));
+        .route("/q", get(brp::get_q_chapter))
+        .route("/translation", post(brp::post_translation))
+        .route("/passage", get(brp::get_passage))
+        .route("/goto", get(brp::get_goto))
+        .route(
+            "/annotations/highlight",
+            post(brp::annotations::post_highlight),
+        )
+        .route(
+            "/annotations/bookmark",
+            post(brp::annotations::post_bookmark),
+        )
+        .route("/annotations/note", post(brp::annotations::post_note))
+        .route(
+            "/annotations/notes/:id/delete",
+            post(brp::annotations::post_delete_note),
+        )
+        .route("/notes", get(brp::annotations::page_notes))
+        .route(
+            "/journal",
+            get(brp::journal::get_journal).post(brp::journal::post_journal),
+        )
+        .route("/export", get(brp::export::get_export))
+        .route("/export/reading-log.csv", get(brp::export::get_reading_log))
+        .route("/import", post(brp::export::post_import))
+        .route("/search", get(brp::search::get_search))
+        .route("/onboarding", post(brp::onboarding::post_onboarding))
+        .route("/stats", get(brp::stats::page_stats))
+        .route("/schedule", get(brp::schedule::page_schedule))
+        .route("/settings", get(brp::settings::page_settings))
+        .route("/settings/readings", post(brp::settings::post_readings))
+        .route("/settings/locale", post(brp::settings::post_locale))
+        .route("/settings/tokens", post(api::tokens::post_token))
+        .route(
+            "/settings/devices/revoke",
+            post(auth::devices::post_revoke_other_devices),
+        )
+        .route(
+            "/settings/devices/:id/revoke",
+            post(auth::devices::post_revoke_device),
+        )
+        .route(
+            "/settings/tokens/:id/revoke",
+            post(api::tokens::post_revoke_token),
+        )
+        .route(
+            "/settings/calendar",
+            post(brp::calendar::post_calendar_token),
+        )
+        .route(
+            "/settings/calendar/revoke",
+            post(brp::calendar::post_calendar_revoke),
+        )
+        .route(
+            "/settings/readings/reset",
+            post(brp::settings::post_readings_reset),
+        );
 
     let router = Router::new()
         .nest("/", brp_router)
         .route("/login", get(login::page_login))
         .route("/logout", post(auth::post_logout))
+        .route("/logout/all", post(auth::devices::post_logout_all))
         .layer(TraceLayer::new_for_http())
         .route("/api/auth/google_callback", get(auth::google_callback))
+        .route(
+            "/api/auth/:provider/callback",
+            get(auth::get_callback).post(auth::post_callback),
+        )
+        .route("/api/auth/:provider/login", post(auth::post_login))
+        .route("/api/auth/:provider/link", post(auth::post_login_link))
+        .nest("/api/v1", api::v1::router())
+        .route("/calendar/:file", get(brp::calendar::get_calendar))
         .nest_service(
             "/static",
             ServeDir::new("./assets/dist/").precompressed_gzip(),
         )
-        .layer(Extension(auth::build_oauth_client(
-            GOOGLE_OAUTH_CLIENT_ID.to_string(),
-            GOOGLE_OAUTH_CLIENT_SECRET.to_string(),
-        )))
         .with_state(state);
 
     // #[cfg(debug_assertions)]
@@ -1,7 +1,33 @@
 use chrono::{Datelike, FixedOffset, NaiveDate, TimeZone, Utc};
 use core::panic;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use std::str::FromStr;
+use thiserror::Error;
 use time::{Date, Month};
 
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    #[error("DATABASE_URL env var must be set")]
+    MissingUrl,
+    #[error("can't open the database: {0}")]
+    Connect(#[from] sqlx::Error),
+    #[error("can't migrate the database: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+}
+
+/// Open the database at `DATABASE_URL`, creating it if needed, and run the
+/// migrations.
+pub async fn open_database() -> Result<SqlitePool, DatabaseError> {
+    let url = std::env::var("DATABASE_URL").map_err(|_| DatabaseError::MissingUrl)?;
+    let options = SqliteConnectOptions::from_str(&url)?.create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(pool)
+}
+
 /// return {day}/{month}/{year}
 pub fn today() -> String {
     let (y, m, d) = today_ymd();
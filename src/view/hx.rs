@@ -10,6 +10,7 @@ pub struct HxCfg<'a> {
     pub swap: Option<&'a str>,
     pub select_oob: Option<&'a str>,
     pub vals: Option<&'a str>,
+    pub include: Option<&'a str>,
     pub script: Option<&'a str>,
     pub disabled_elt: Option<&'a str>,
 }
@@ -54,6 +55,11 @@ impl<'a> HxCfg<'a> {
         self
     }
 
+    pub fn with_include(mut self, val: &'a str) -> Self {
+        self.include = Some(val);
+        self
+    }
+
     pub fn with_disabled_elt(mut self, val: &'a str) -> Self {
         self.disabled_elt = Some(val);
         self
@@ -0,0 +1,47 @@
+use maud::{Markup, PreEscaped};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// Render Markdown written by users. Raw HTML is shown as text and links can't
+/// run scripts.
+pub fn markdown(text: &str) -> Markup {
+    let parser = Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+
+    let mut out = String::new();
+    html::push_html(&mut out, parser);
+    PreEscaped(out)
+}
+
+fn safe_url(url: CowStr) -> CowStr {
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme.trim().to_ascii_lowercase());
+    match scheme.as_deref() {
+        Some("javascript" | "vbscript" | "data") => CowStr::Borrowed("#"),
+        _ => url,
+    }
+}
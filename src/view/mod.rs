@@ -1,3 +1,4 @@
 pub mod hx;
+pub mod markdown;
 pub mod pages;
 pub mod ui;
@@ -4,53 +4,85 @@ use super::super::ui::{
     Color,
 };
 use crate::{
-    auth::{self, User},
+    auth::{
+        providers::{email::LINK_MINUTES, LoginMethod},
+        User,
+    },
     view::{hx::HxCfg, ui::button::ButtonCfg},
+    AppState,
 };
-use axum::response::{IntoResponse, Redirect};
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect},
+};
+use axum_extra::extract::PrivateCookieJar;
 use maud::{html, Markup};
 
-pub async fn page_login(user: Option<User>) -> impl IntoResponse {
+pub async fn page_login(
+    State(state): State<AppState>,
+    user: Option<User>,
+    jar: PrivateCookieJar,
+) -> impl IntoResponse {
     if user.is_some() {
         return Redirect::to("/").into_response();
     }
 
-    let google_auth_url = format!(
-        "https://accounts.google.com/o/oauth2/v2/auth?response_type=code&scope={scope}&client_id={client}&redirect_uri={redirect}&access_type=offline",
-        client = auth::GOOGLE_OAUTH_CLIENT_ID.as_str(),
-        redirect = auth::GOOGLE_OAUTH_REDIRECT_URL.as_str(),
-        scope = url_escape::encode_fragment(&auth::GOOGLE_OAUTH_SCOPE.as_str())
-    );
-    super::page(
+    let mut jar = jar;
+    let mut logins = Vec::new();
+    for provider in state.providers.iter() {
+        let (next, method) = provider.login(jar);
+        jar = next;
+        logins.push((provider, method));
+    }
+
+    let page = super::page(
         "Login",
         html! {
             div class="flex flex-col justify-center items-center h-screen" {
                 div class="w-[350px] mt-10 border border-border bg-background/70 shadow-md z-10 rounded-sm px-6 pt-8 pb-6" {
                     h1 class="text-xl font-bold flex justify-center" {"Login"}
-                    (ui_button(html! { "Login with Google" },
-                        &ButtonCfg::new()
-                            .append_icon("/static/img/icons8-google.svg", "h-5 w-5")
-                            .with_cn("mt-10 w-full py-6 border-foreground/20 bg-background-100 hover:bg-background-100/50")
-                            .with_color(Color::Alternative)
-                            .as_link(&google_auth_url),
-                        &HxCfg::new()
-                    ))
+                    @for (provider, method) in &logins {
+                        @match method {
+                            LoginMethod::Redirect(url) => {
+                                @let button = ButtonCfg::new()
+                                    .with_cn("mt-10 w-full py-6 border-foreground/20 bg-background-100 hover:bg-background-100/50")
+                                    .with_color(Color::Alternative)
+                                    .as_link(url);
+                                @let button = match provider.icon() {
+                                    Some(icon) => button.append_icon(icon, "h-5 w-5"),
+                                    None => button,
+                                };
+                                (ui_button(html! { "Login with " (provider.name()) }, &button, &HxCfg::new()))
+                            }
+                            LoginMethod::Credentials => (credential_form(provider.slug())),
+                            LoginMethod::EmailLink => (email_link_form(provider.slug())),
+                        }
+                    }
                 }
             }
         },
-    ).into_response()
+    );
+    (jar, page).into_response()
+}
+
+/// Each form on the login page shows its own errors.
+fn error_id(provider: &str) -> String {
+    format!("{provider}-login-error")
 }
 
-pub fn credential_form() -> Markup {
-    let script = "on input add .invisible to #error-message";
+/// Hides the error of the form being typed in.
+const HIDE_ERROR_SCRIPT: &str = "on input add .invisible to <.error-message/> in closest <form/>";
+
+pub fn credential_form(provider: &str) -> Markup {
+    let script = HIDE_ERROR_SCRIPT;
     let pre_escaped = html! {
         form id="login-form"
             class="mt-8 flex flex-col gap-2"
-            hx-post="/login" hx-target="#error-message" hx-swap="outerHTML"
+            hx-post=(format!("/api/auth/{provider}/login")) hx-target=(format!("#{}", error_id(provider))) hx-swap="outerHTML"
             hx-indicator="#indicator"
             hx-disabled-elt="#submit"
         {
-            (ui_input("username", &InputCfgBuilder::new().with_label("Username").with_autocomplete("username").with_script(script).autofocus(true).spellcheck(false).build()))
+            (ui_input("email", &InputCfgBuilder::new().with_label("Email").with_autocomplete("username").with_script(script).autofocus(true).spellcheck(false).build()))
             (ui_input("password", &InputCfgBuilder::new().with_label("Password").with_autocomplete("current-password").with_script(script).with_ccn("mt-4").with_type(InputType::Password).build()))
             div class="mt-4" {
                 (ui_button(
@@ -63,7 +95,7 @@ pub fn credential_form() -> Markup {
                     &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit).with_id("submit").with_cn("w-24"),
                     &HxCfg::new()
                 ))
-                p id="error-message" class="text-sm mt-2 text-destructive invisible" {
+                p id=(error_id(provider)) class="error-message text-sm mt-2 text-destructive invisible" {
                     "error message placeholder"
                 }
             }
@@ -72,6 +104,70 @@ pub fn credential_form() -> Markup {
     pre_escaped
 }
 
+/// Asks for the address to mail a login link to.
+pub fn email_link_form(provider: &str) -> Markup {
+    let script = HIDE_ERROR_SCRIPT;
+    html! {
+        form id="email-link-form"
+            class="mt-8 flex flex-col gap-2"
+            hx-post=(format!("/api/auth/{provider}/link")) hx-target=(format!("#{}", error_id(provider))) hx-swap="outerHTML"
+            hx-disabled-elt="#send-link"
+        {
+            (ui_input("email", InputCfgBuilder::new().with_label("Email").with_autocomplete("email").with_script(script).spellcheck(false).build()))
+            div class="mt-4" {
+                (ui_button(html! { "Email me a login link" },
+                    &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit).with_id("send-link"),
+                    &HxCfg::new()
+                ))
+                p id=(error_id(provider)) class="error-message text-sm mt-2 text-destructive invisible" {
+                    "error message placeholder"
+                }
+            }
+        }
+    }
+}
+
+/// Opened from a login link, posting its token back to log in.
+pub fn page_confirm_login_link(provider: &str, token: &str) -> Markup {
+    super::page(
+        "Login",
+        html! {
+            div class="flex flex-col justify-center items-center h-screen" {
+                div class="w-[350px] mt-10 border border-border bg-background/70 shadow-md z-10 rounded-sm px-6 pt-8 pb-6" {
+                    h1 class="text-xl font-bold flex justify-center" {"Login"}
+                    form class="mt-8 flex flex-col gap-4" method="post"
+                        action=(format!("/api/auth/{provider}/callback"))
+                    {
+                        p class="text-sm" { "Log in to the Bible reading plan in this browser?" }
+                        input type="hidden" name="token" value=(token);
+                        (ui_button(html! { "Log in" },
+                            &ButtonCfg::new().with_color(Color::Default).with_type(ButtonType::Submit).with_cn("w-24"),
+                            &HxCfg::new()
+                        ))
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Replaces the error message of `email_link_form` once the link is sent.
+pub fn fragment_login_link_sent(provider: &str, email: &str) -> Markup {
+    html! {
+        p id=(error_id(provider)) class="error-message text-sm mt-2" {
+            "A login link is on its way to " (email) ". It works once, within "
+            (LINK_MINUTES) " minutes."
+        }
+    }
+}
+
+/// Replaces the error message of `credential_form` and `email_link_form`.
+pub fn fragment_login_error(provider: &str, message: &str) -> Markup {
+    html! {
+        p id=(error_id(provider)) class="error-message text-sm mt-2 text-destructive" { (message) }
+    }
+}
+
 pub fn redirect_login() -> Markup {
     html! {
         head {
@@ -35,6 +35,7 @@ pub fn ui_button(mut child: Markup, cfg: &ButtonCfg, hx: &HxCfg) -> Markup {
                 hx-target=[hx.target]
                 hx-swap=[hx.swap]
                 hx-vals=[hx.vals]
+                hx-include=[hx.include]
                 hx-disabled-elt=[hx.disabled_elt]
                 "x-on:click"=[cfg.x_on_click]
                 "x-on:click.outside"=[cfg.x_on_click_outside]
@@ -57,6 +58,7 @@ pub fn ui_button(mut child: Markup, cfg: &ButtonCfg, hx: &HxCfg) -> Markup {
                 hx-swap=[hx.swap]
                 hx-select-oob=[hx.select_oob]
                 hx-vals=[hx.vals]
+                hx-include=[hx.include]
                 hx-disabled-elt=[hx.disabled_elt]
                 "x-on:click"=[cfg.x_on_click]
                 "x-on:click.outside"=[cfg.x_on_click_outside]
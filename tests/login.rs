@@ -0,0 +1,202 @@
+//! Logging in end to end with the providers that need no outside service: a
+//! local password and a login link written to a directory.
+use axum::{
+    body::to_bytes,
+    extract::{Path, Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::Response,
+    Form,
+};
+use axum_extra::extract::PrivateCookieJar;
+use brp_web::{
+    auth::{
+        self,
+        providers::{
+            password::set_password, EmailLinkProvider, IdentityProviders, PasswordProvider,
+        },
+        session::SESSION_COOKIE,
+    },
+    brp::content::BibleRegistry,
+    mail::{FileTransport, Mail, MailError, MailTransport},
+    AppState,
+};
+use cookie::Key;
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use std::{collections::HashMap, sync::Arc};
+
+/// A fresh in-memory database, one connection so every query sees the same one.
+async fn database() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!().run(&pool).await.unwrap();
+    pool
+}
+
+async fn app_state(providers: IdentityProviders) -> AppState {
+    let db = database().await;
+    AppState {
+        bibles: BibleRegistry::load(&db, None).await.unwrap(),
+        db,
+        key: Key::generate(),
+        providers,
+    }
+}
+
+fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn sets_session(response: &Response) -> bool {
+    response.headers().get_all(SET_COOKIE).iter().any(|cookie| {
+        cookie
+            .to_str()
+            .unwrap()
+            .starts_with(&format!("{SESSION_COOKIE}="))
+    })
+}
+
+async fn body(response: Response) -> String {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+async fn sessions(db: &SqlitePool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+        .fetch_one(db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn password_login() {
+    let mut providers = IdentityProviders::default();
+    providers.add(PasswordProvider);
+    let state = app_state(providers).await;
+    set_password(&state.db, " Reader@Example.com ", "a long password")
+        .await
+        .unwrap();
+
+    let login = |email: &str, password: &str| {
+        auth::post_login(
+            State(state.clone()),
+            PrivateCookieJar::new(state.key.clone()),
+            HeaderMap::new(),
+            Path("local".to_string()),
+            Form(params(&[("email", email), ("password", password)])),
+        )
+    };
+
+    let response = login("reader@example.com", "wrong password").await.unwrap();
+    assert!(!sets_session(&response));
+    assert!(body(response).await.contains("Wrong email or password"));
+    assert_eq!(sessions(&state.db).await, 0);
+
+    let response = login("READER@example.com", "a long password")
+        .await
+        .unwrap();
+    assert!(sets_session(&response));
+    assert_eq!(response.headers()["HX-Redirect"], "/");
+    assert_eq!(sessions(&state.db).await, 1);
+}
+
+/// The token of the login link in the only mail in `dir`.
+fn mailed_token(dir: &std::path::Path) -> String {
+    let mut mails: Vec<_> = std::fs::read_dir(dir).unwrap().collect();
+    assert_eq!(mails.len(), 1);
+    let mail = std::fs::read_to_string(mails.pop().unwrap().unwrap().path()).unwrap();
+    assert!(mail.starts_with("To: reader@example.com\n"));
+    let (_, token) = mail.split_once("?token=").unwrap();
+    token.split_whitespace().next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn email_link_login() {
+    let dir = std::env::temp_dir().join(format!("brp-web-mail-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut providers = IdentityProviders::default();
+    providers.set_email_link(EmailLinkProvider::new(
+        Arc::new(FileTransport::new(&dir)),
+        "http://localhost:3000/",
+    ));
+    let state = app_state(providers).await;
+
+    let email_link = state.providers.email_link().unwrap();
+    assert!(email_link
+        .send_link(&state.db, "reader@example.com")
+        .await
+        .unwrap());
+    let token = mailed_token(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Opening the link only asks to confirm, so mail scanners don't use it up.
+    let response = auth::get_callback(
+        State(state.clone()),
+        PrivateCookieJar::new(state.key.clone()),
+        HeaderMap::new(),
+        Path("email".to_string()),
+        Query(params(&[("token", &token)])),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!sets_session(&response));
+    assert!(body(response).await.contains(&token));
+
+    let confirm = || {
+        auth::post_callback(
+            State(state.clone()),
+            PrivateCookieJar::new(state.key.clone()),
+            HeaderMap::new(),
+            Path("email".to_string()),
+            Form(params(&[("token", &token)])),
+        )
+    };
+    let response = confirm().await.unwrap();
+    assert!(response.status().is_redirection());
+    assert!(sets_session(&response));
+    assert_eq!(sessions(&state.db).await, 1);
+
+    // Links work once.
+    let response = confirm().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(!sets_session(&response));
+}
+
+/// Fails every send, like an unreachable SMTP server.
+struct BrokenTransport;
+
+#[axum::async_trait]
+impl MailTransport for BrokenTransport {
+    async fn send(&self, _mail: &Mail) -> Result<(), MailError> {
+        Err(std::io::Error::other("unreachable").into())
+    }
+}
+
+#[tokio::test]
+async fn unsent_links_are_not_kept() {
+    let mut providers = IdentityProviders::default();
+    providers.set_email_link(EmailLinkProvider::new(
+        Arc::new(BrokenTransport),
+        "http://localhost:3000/",
+    ));
+    let state = app_state(providers).await;
+
+    let email_link = state.providers.email_link().unwrap();
+    for _ in 0..4 {
+        assert!(email_link
+            .send_link(&state.db, "reader@example.com")
+            .await
+            .is_err());
+    }
+    let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM login_links")
+        .fetch_one(&state.db)
+        .await
+        .unwrap();
+    assert_eq!(links, 0);
+}